@@ -1,8 +1,10 @@
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use nitor_vault::cloudformation::CloudFormationStackData;
 use nitor_vault::errors::VaultError;
-use nitor_vault::{CreateStackResult, UpdateStackResult, Value, Vault};
+use nitor_vault::{CreateStackResult, LocalVault, UpdateStackResult, Value, Vault};
+use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use tokio::runtime::Runtime;
 
@@ -11,6 +13,135 @@ fn vault_error_to_anyhow(err: VaultError) -> anyhow::Error {
     err.into()
 }
 
+/// `Runtime` shared by every call site in this module.
+///
+/// The module-level free functions and [`PyVault`] both only ever block on
+/// this runtime for the duration of a single call, so one runtime can safely
+/// be reused instead of paying tokio's startup cost on every operation.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+fn runtime() -> PyResult<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Runtime::new()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// A `Vault` handle that resolves the stack/bucket/key/region/profile once
+/// in `__new__` and reuses the resulting client for every subsequent method
+/// call, instead of re-resolving AWS config and CloudFormation parameters on
+/// every operation like the module-level free functions do.
+#[pyclass(name = "Vault")]
+struct PyVault {
+    vault: Vault,
+}
+
+#[pymethods]
+impl PyVault {
+    #[new]
+    #[pyo3(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        vault_stack: Option<String>,
+        region: Option<String>,
+        bucket: Option<String>,
+        key: Option<String>,
+        prefix: Option<String>,
+        profile: Option<String>,
+        endpoint: Option<String>,
+        key_passphrase: Option<String>,
+        sse_c_key: Option<String>,
+        role_arn: Option<String>,
+        role_session_name: Option<String>,
+        role_external_id: Option<String>,
+    ) -> PyResult<Self> {
+        let vault = runtime()?.block_on(async {
+            Vault::new(
+                vault_stack,
+                region,
+                bucket,
+                key,
+                prefix,
+                endpoint,
+                key_passphrase,
+                sse_c_key,
+                profile,
+                role_arn,
+                role_session_name,
+                role_external_id,
+            )
+            .await
+            .map_err(vault_error_to_anyhow)
+        })?;
+        Ok(Self { vault })
+    }
+
+    fn store(&self, name: &str, value: &[u8], compress: bool) -> PyResult<()> {
+        runtime()?.block_on(async {
+            Ok(Box::pin(self.vault.store(name, value, compress))
+                .await
+                .map_err(vault_error_to_anyhow)?)
+        })
+    }
+
+    fn lookup(&self, name: &str) -> PyResult<String> {
+        runtime()?.block_on(async {
+            let result = Box::pin(self.vault.lookup(name))
+                .await
+                .map_err(vault_error_to_anyhow)?;
+            Ok(result.to_string())
+        })
+    }
+
+    fn delete(&self, name: &str) -> PyResult<()> {
+        runtime()?.block_on(async { Ok(self.vault.delete(name).await.map_err(vault_error_to_anyhow)?) })
+    }
+
+    fn delete_many(&self, names: Vec<String>) -> PyResult<()> {
+        runtime()?
+            .block_on(async { Ok(self.vault.delete_many(&names).await.map_err(vault_error_to_anyhow)?) })
+    }
+
+    fn exists(&self, name: &str) -> PyResult<bool> {
+        runtime()?.block_on(async { Ok(self.vault.exists(name).await.map_err(vault_error_to_anyhow)?) })
+    }
+
+    fn all(&self) -> PyResult<Vec<String>> {
+        runtime()?.block_on(async { Ok(self.vault.all().await.map_err(vault_error_to_anyhow)?) })
+    }
+
+    fn rotate(&self, name: &str, new_key: Option<String>) -> PyResult<()> {
+        runtime()?.block_on(async {
+            Ok(self
+                .vault
+                .rotate(name, new_key.as_deref())
+                .await
+                .map_err(vault_error_to_anyhow)?)
+        })
+    }
+
+    fn rotate_all(&self, new_key: Option<String>) -> PyResult<Vec<String>> {
+        runtime()?.block_on(async {
+            Ok(self
+                .vault
+                .rotate_all(new_key.as_deref())
+                .await
+                .map_err(vault_error_to_anyhow)?)
+        })
+    }
+}
+
+/// Build a `LocalVault` for `local_path`, reading the passphrase from
+/// `VAULT_LOCAL_PASSPHRASE` since the offline vault has no AWS credentials
+/// to derive one from.
+fn local_vault(local_path: String) -> PyResult<LocalVault> {
+    let passphrase = std::env::var("VAULT_LOCAL_PASSPHRASE").map_err(|_| {
+        PyRuntimeError::new_err("VAULT_LOCAL_PASSPHRASE must be set when using local_path")
+    })?;
+    Ok(LocalVault::new(local_path, passphrase))
+}
+
 fn to_hash_map(stack_data: CloudFormationStackData, result: String) -> HashMap<String, String> {
     let mut map = HashMap::new();
     map.insert("result".to_string(), result);
@@ -43,7 +174,8 @@ fn to_hash_map(stack_data: CloudFormationStackData, result: String) -> HashMap<S
     map
 }
 
-#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, local_path=None))]
+#[allow(clippy::too_many_arguments)]
 fn delete(
     name: &str,
     vault_stack: Option<String>,
@@ -52,21 +184,48 @@ fn delete(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    local_path: Option<String>,
 ) -> PyResult<()> {
-    Runtime::new()?.block_on(async {
-        Ok(
-            Vault::new(vault_stack, region, bucket, key, prefix, profile)
-                .await
-                .map_err(vault_error_to_anyhow)?
+    if let Some(local_path) = local_path {
+        return runtime()?.block_on(async {
+            Ok(local_vault(local_path)?
                 .delete(name)
                 .await
-                .map_err(vault_error_to_anyhow)?,
+                .map_err(vault_error_to_anyhow)?)
+        });
+    }
+    runtime()?.block_on(async {
+        Ok(Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
         )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .delete(name)
+        .await
+        .map_err(vault_error_to_anyhow)?)
     })
 }
 
-#[pyfunction(signature = (names, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (names, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None))]
 #[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
 fn delete_many(
     names: Vec<String>,
     vault_stack: Option<String>,
@@ -75,20 +234,38 @@ fn delete_many(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
 ) -> PyResult<()> {
-    Runtime::new()?.block_on(async {
-        Ok(
-            Vault::new(vault_stack, region, bucket, key, prefix, profile)
-                .await
-                .map_err(vault_error_to_anyhow)?
-                .delete_many(&names)
-                .await
-                .map_err(vault_error_to_anyhow)?,
+    runtime()?.block_on(async {
+        Ok(Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
         )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .delete_many(&names)
+        .await
+        .map_err(vault_error_to_anyhow)?)
     })
 }
 
-#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, local_path=None))]
+#[allow(clippy::too_many_arguments)]
 fn exists(
     name: &str,
     vault_stack: Option<String>,
@@ -97,30 +274,69 @@ fn exists(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    local_path: Option<String>,
 ) -> PyResult<bool> {
-    Runtime::new()?.block_on(async {
-        let result: bool = Vault::new(vault_stack, region, bucket, key, prefix, profile)
-            .await
-            .map_err(vault_error_to_anyhow)?
-            .exists(name)
-            .await
-            .map_err(vault_error_to_anyhow)?;
+    if let Some(local_path) = local_path {
+        return runtime()?.block_on(async {
+            Ok(local_vault(local_path)?
+                .exists(name)
+                .await
+                .map_err(vault_error_to_anyhow)?)
+        });
+    }
+    runtime()?.block_on(async {
+        let result: bool = Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .exists(name)
+        .await
+        .map_err(vault_error_to_anyhow)?;
 
         Ok(result)
     })
 }
 
-#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, profile=None))]
+#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, profile=None, role_arn=None, role_session_name=None, role_external_id=None))]
 fn init(
     vault_stack: Option<String>,
     region: Option<String>,
     bucket: Option<String>,
     profile: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
 ) -> PyResult<HashMap<String, String>> {
-    Runtime::new()?.block_on(async {
-        let result = Vault::init(vault_stack, region, bucket, profile)
-            .await
-            .map_err(vault_error_to_anyhow)?;
+    runtime()?.block_on(async {
+        let result = Vault::init(
+            vault_stack,
+            region,
+            bucket,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?;
         match result {
             CreateStackResult::Exists { data } => Ok(to_hash_map(data, "exists".to_string())),
             CreateStackResult::ExistsWithFailedState { data } => {
@@ -142,7 +358,8 @@ fn init(
     })
 }
 
-#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, local_path=None))]
+#[allow(clippy::too_many_arguments)]
 fn list_all(
     vault_stack: Option<String>,
     region: Option<String>,
@@ -150,20 +367,45 @@ fn list_all(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    local_path: Option<String>,
 ) -> PyResult<Vec<String>> {
-    Runtime::new()?.block_on(async {
-        let result = Vault::new(vault_stack, region, bucket, key, prefix, profile)
-            .await
-            .map_err(vault_error_to_anyhow)?
-            .all()
-            .await
-            .map_err(vault_error_to_anyhow)?;
+    if let Some(local_path) = local_path {
+        return runtime()?
+            .block_on(async { Ok(local_vault(local_path)?.all().await.map_err(vault_error_to_anyhow)?) });
+    }
+    runtime()?.block_on(async {
+        let result = Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .all()
+        .await
+        .map_err(vault_error_to_anyhow)?;
 
         Ok(result)
     })
 }
 
-#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, local_path=None))]
+#[allow(clippy::too_many_arguments)]
 fn lookup(
     name: &str,
     vault_stack: Option<String>,
@@ -172,13 +414,42 @@ fn lookup(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    local_path: Option<String>,
 ) -> PyResult<String> {
-    Runtime::new()?.block_on(async {
-        let result: Value = Box::pin(
-            Vault::new(vault_stack, region, bucket, key, prefix, profile)
+    if let Some(local_path) = local_path {
+        return runtime()?.block_on(async {
+            let result = local_vault(local_path)?
+                .lookup(name)
                 .await
-                .map_err(vault_error_to_anyhow)?
-                .lookup(name),
+                .map_err(vault_error_to_anyhow)?;
+            Ok(result.to_string())
+        });
+    }
+    runtime()?.block_on(async {
+        let result: Value = Box::pin(
+            Vault::new(
+                vault_stack,
+                region,
+                bucket,
+                key,
+                prefix,
+                endpoint,
+                key_passphrase,
+                sse_c_key,
+                profile,
+                role_arn,
+                role_session_name,
+                role_external_id,
+            )
+            .await
+            .map_err(vault_error_to_anyhow)?
+            .lookup(name),
         )
         .await
         .map_err(vault_error_to_anyhow)?;
@@ -190,13 +461,14 @@ fn lookup(
 #[pyfunction]
 /// Run Vault CLI with given args.
 fn run(args: Vec<String>) -> PyResult<()> {
-    Runtime::new()?.block_on(async {
+    runtime()?.block_on(async {
         nitor_vault::run_cli_with_args(args).await?;
         Ok(())
     })
 }
 
-#[pyfunction(signature = (name, value, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (name, value, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, local_path=None, compress=false))]
+#[allow(clippy::too_many_arguments)]
 fn store(
     name: &str,
     value: &[u8],
@@ -206,20 +478,133 @@ fn store(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    local_path: Option<String>,
+    compress: bool,
 ) -> PyResult<()> {
-    Runtime::new()?.block_on(async {
-        Ok(Box::pin(
-            Vault::new(vault_stack, region, bucket, key, prefix, profile)
+    if let Some(local_path) = local_path {
+        return runtime()?.block_on(async {
+            Ok(local_vault(local_path)?
+                .store(name, value)
                 .await
-                .map_err(vault_error_to_anyhow)?
-                .store(name, value),
+                .map_err(vault_error_to_anyhow)?)
+        });
+    }
+    runtime()?.block_on(async {
+        Ok(Box::pin(
+            Vault::new(
+                vault_stack,
+                region,
+                bucket,
+                key,
+                prefix,
+                endpoint,
+                key_passphrase,
+                sse_c_key,
+                profile,
+                role_arn,
+                role_session_name,
+                role_external_id,
+            )
+            .await
+            .map_err(vault_error_to_anyhow)?
+            .store(name, value, compress),
         )
         .await
         .map_err(vault_error_to_anyhow)?)
     })
 }
 
-#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None))]
+#[pyfunction(signature = (name, vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, new_key=None))]
+#[allow(clippy::too_many_arguments)]
+fn rotate(
+    name: &str,
+    vault_stack: Option<String>,
+    region: Option<String>,
+    bucket: Option<String>,
+    key: Option<String>,
+    prefix: Option<String>,
+    profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    new_key: Option<String>,
+) -> PyResult<()> {
+    runtime()?.block_on(async {
+        Ok(Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .rotate(name, new_key.as_deref())
+        .await
+        .map_err(vault_error_to_anyhow)?)
+    })
+}
+
+#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None, new_key=None))]
+#[allow(clippy::too_many_arguments)]
+fn rotate_all(
+    vault_stack: Option<String>,
+    region: Option<String>,
+    bucket: Option<String>,
+    key: Option<String>,
+    prefix: Option<String>,
+    profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+    new_key: Option<String>,
+) -> PyResult<Vec<String>> {
+    runtime()?.block_on(async {
+        let result = Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?
+        .rotate_all(new_key.as_deref())
+        .await
+        .map_err(vault_error_to_anyhow)?;
+
+        Ok(result)
+    })
+}
+
+#[pyfunction(signature = (vault_stack=None, region=None, bucket=None, key=None, prefix=None, profile=None, endpoint=None, key_passphrase=None, sse_c_key=None, role_arn=None, role_session_name=None, role_external_id=None))]
+#[allow(clippy::too_many_arguments)]
 fn update(
     vault_stack: Option<String>,
     region: Option<String>,
@@ -227,11 +612,30 @@ fn update(
     key: Option<String>,
     prefix: Option<String>,
     profile: Option<String>,
+    endpoint: Option<String>,
+    key_passphrase: Option<String>,
+    sse_c_key: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
 ) -> PyResult<HashMap<String, String>> {
-    Runtime::new()?.block_on(async {
-        let result = Vault::new(vault_stack, region, bucket, key, prefix, profile)
-            .await
-            .map_err(vault_error_to_anyhow)?
+    runtime()?.block_on(async {
+        let result = Vault::new(
+            vault_stack,
+            region,
+            bucket,
+            key,
+            prefix,
+            endpoint,
+            key_passphrase,
+            sse_c_key,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await
+        .map_err(vault_error_to_anyhow)?
             .update_stack()
             .await
             .map_err(vault_error_to_anyhow)?;
@@ -257,12 +661,15 @@ fn update(
 #[pymodule]
 #[pyo3(name = "nitor_vault_rs")]
 fn nitor_vault_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVault>()?;
     m.add_function(wrap_pyfunction!(delete, m)?)?;
     m.add_function(wrap_pyfunction!(delete_many, m)?)?;
     m.add_function(wrap_pyfunction!(exists, m)?)?;
     m.add_function(wrap_pyfunction!(init, m)?)?;
     m.add_function(wrap_pyfunction!(list_all, m)?)?;
     m.add_function(wrap_pyfunction!(lookup, m)?)?;
+    m.add_function(wrap_pyfunction!(rotate, m)?)?;
+    m.add_function(wrap_pyfunction!(rotate_all, m)?)?;
     m.add_function(wrap_pyfunction!(run, m)?)?;
     m.add_function(wrap_pyfunction!(store, m)?)?;
     m.add_function(wrap_pyfunction!(update, m)?)?;
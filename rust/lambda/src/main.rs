@@ -43,8 +43,24 @@ async fn function_handler(
         .await;
     };
 
+    // Parse the requested response encoding from the CloudFormation request
+    let encoding = match response_encoding(&resource_properties) {
+        Ok(encoding) => encoding,
+        Err(e) => {
+            let error_message = format!("{e}");
+            error!("{error_message}");
+            return send_response(
+                event_data,
+                CloudFormationCustomResourceResponseStatus::Failed,
+                None,
+                Some(error_message),
+            )
+            .await;
+        }
+    };
+
     // Try to decrypt the ciphertext using KMS
-    match decrypt_ciphertext(&kms_client, &ciphertext).await {
+    match decrypt_ciphertext(&kms_client, &ciphertext, encoding).await {
         Ok(plaintext) => {
             let message = "Decrypt successful".to_string();
             info!(message);
@@ -109,8 +125,32 @@ fn extract_event_data(
     }
 }
 
+/// Read the `ResponseEncoding` resource property, defaulting to `"utf8"`
+/// when it's absent.
+fn response_encoding(resource_properties: &Value) -> Result<&'static str, Error> {
+    match resource_properties.get("ResponseEncoding").and_then(Value::as_str) {
+        None | Some("utf8") => Ok("utf8"),
+        Some("base64") => Ok("base64"),
+        Some(other) => Err(format!(
+            "Invalid ResponseEncoding '{other}', expected 'utf8' or 'base64'"
+        )
+        .into()),
+    }
+}
+
 /// Decrypt a base64-encoded ciphertext using AWS KMS.
-async fn decrypt_ciphertext(kms_client: &KmsClient, ciphertext: &str) -> Result<String, Error> {
+///
+/// `encoding` is `"utf8"` (the default) or `"base64"`, from the
+/// `ResponseEncoding` resource property. `"utf8"` requires the plaintext
+/// to be valid UTF-8 and fails with a distinct reason otherwise;
+/// `"base64"` returns the raw plaintext bytes base64-encoded regardless,
+/// so binary secrets like keys or certificates can still be retrieved
+/// through the custom resource.
+async fn decrypt_ciphertext(
+    kms_client: &KmsClient,
+    ciphertext: &str,
+    encoding: &str,
+) -> Result<String, Error> {
     let decoded_ciphertext = general_purpose::STANDARD.decode(ciphertext)?;
 
     // Decrypt the ciphertext using KMS
@@ -120,14 +160,19 @@ async fn decrypt_ciphertext(kms_client: &KmsClient, ciphertext: &str) -> Result<
         .send()
         .await?;
 
-    // Convert the decrypted plaintext to a string
     let plaintext = match response.plaintext {
         None => return Err("Plaintext is missing in the response".into()),
         Some(blob) => blob.into_inner(),
     };
 
-    // TODO: does this need to support binary data?
-    Ok(String::from_utf8(plaintext)?)
+    if encoding == "base64" {
+        return Ok(general_purpose::STANDARD.encode(plaintext));
+    }
+
+    String::from_utf8(plaintext).map_err(|_| {
+        "Plaintext is not valid UTF-8; set the ResponseEncoding resource property to 'base64' to retrieve binary data"
+            .into()
+    })
 }
 
 /// Sends a response to the `CloudFormation` `ResponseURL`
@@ -0,0 +1,497 @@
+//! Storage backend abstraction.
+//!
+//! `Vault` encrypts and wraps secrets using the KMS envelope format
+//! documented in `lib.rs`, but doesn't need to know how the resulting
+//! blobs are actually persisted. The [`StorageBackend`] trait pulls the
+//! raw object operations (put/get/delete/list/exists) out from under
+//! that logic, so alternative object stores can be plugged in without
+//! touching the encryption code.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{
+    CompletedMultipartUpload, CompletedPart, Delete, ObjectCannedAcl, ObjectIdentifier,
+};
+use aws_sdk_s3::Client as S3Client;
+use base64::Engine;
+use futures_util::TryStreamExt;
+
+use crate::errors::VaultError;
+
+/// Ciphertext bodies larger than this use multipart upload instead of a
+/// single `put_object` call, to stay well under S3's 5 GiB single-PUT
+/// limit and avoid buffering the whole body into one HTTP request.
+const MULTIPART_THRESHOLD: usize = 8 * 1024 * 1024;
+/// Size of each part in a multipart upload. Must be at least 5 MiB, S3's
+/// minimum part size for all but the last part.
+const MULTIPART_PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// Raw blob operations `Vault` needs from an object store.
+///
+/// `Vault` holds its transport as a `Box<dyn StorageBackend>`, so the KMS
+/// data-key wrapping and AES-GCM envelope format don't depend on it.
+/// `Vault::new` always builds an [`S3Backend`]; `Vault` has no constructor
+/// that takes another implementation.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Store `body` under `key`, overwriting any existing object.
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), VaultError>;
+
+    /// Fetch the full contents of the object stored at `key`.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, VaultError>;
+
+    /// Delete all objects in `keys`. Missing keys are not an error.
+    async fn delete_many(&self, keys: &[String]) -> Result<(), VaultError>;
+
+    /// List every object key currently in the store, invoking `on_page`
+    /// once per page of results as they arrive instead of buffering the
+    /// whole listing before returning anything. Backends that naturally
+    /// paginate (S3, truncated at 1000 keys per response) stream page by
+    /// page, so a bucket with more than one page still enumerates fully
+    /// and a caller (e.g. the CLI) can render results incrementally;
+    /// others just invoke `on_page` once with everything they have.
+    async fn list_paged(
+        &self,
+        on_page: &mut (dyn FnMut(Vec<String>) + Send),
+    ) -> Result<(), VaultError>;
+
+    /// List every object key currently in the store.
+    async fn list(&self) -> Result<Vec<String>, VaultError> {
+        let mut keys = Vec::new();
+        self.list_paged(&mut |page| keys.extend(page)).await?;
+        Ok(keys)
+    }
+
+    /// Check whether an object exists at `key`.
+    async fn exists(&self, key: &str) -> Result<bool, VaultError>;
+
+    /// Mint a time-limited, unauthenticated GET URL for `key`, valid for
+    /// `expires_in`, that a downstream system can fetch without holding
+    /// credentials for this store. Backends with no notion of a
+    /// shareable URL (e.g. [`InMemoryBackend`]) return a [`VaultError`].
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, VaultError>;
+}
+
+/// Customer-provided SSE-C key used to have S3 additionally encrypt the
+/// (already client-side encrypted) blobs at rest, under a key S3 itself
+/// never stores. Layered independently on top of the KMS/Argon2id
+/// envelope, not a replacement for it.
+#[derive(Clone)]
+struct SseCustomerKey {
+    /// Base64-encoded raw 32-byte key, as the `x-amz-server-side-encryption-customer-key` header expects.
+    key_base64: String,
+    /// Base64-encoded MD5 digest of the raw key, as S3 uses to verify it wasn't corrupted in transit.
+    key_md5_base64: String,
+}
+
+impl fmt::Debug for SseCustomerKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SseCustomerKey").finish_non_exhaustive()
+    }
+}
+
+impl SseCustomerKey {
+    fn new(key: &[u8; 32]) -> Self {
+        Self {
+            key_base64: base64::engine::general_purpose::STANDARD.encode(key),
+            key_md5_base64: base64::engine::general_purpose::STANDARD.encode(md5::compute(key).0),
+        }
+    }
+}
+
+/// Default `StorageBackend` backed by AWS S3, or any S3-compatible
+/// endpoint (MinIO, Garage, Ceph, ...) when `endpoint_url` is set on the
+/// client passed to [`S3Backend::new`].
+#[derive(Debug, Clone)]
+pub struct S3Backend {
+    client: S3Client,
+    bucket: String,
+    sse_c: Option<SseCustomerKey>,
+}
+
+impl S3Backend {
+    #[must_use]
+    pub fn new(client: S3Client, bucket: String) -> Self {
+        Self {
+            client,
+            bucket,
+            sse_c: None,
+        }
+    }
+
+    /// Have S3 additionally encrypt every object at rest under `key` (32
+    /// raw bytes), as a defense-in-depth layer on top of the client-side
+    /// envelope encryption `Vault` already applies.
+    #[must_use]
+    pub fn with_sse_c_key(mut self, key: &[u8; 32]) -> Self {
+        self.sse_c = Some(SseCustomerKey::new(key));
+        self
+    }
+
+    /// Upload `body` as a multipart upload, split into [`MULTIPART_PART_SIZE`]
+    /// parts. Aborts the upload on any failure so no dangling multipart
+    /// upload is left incurring storage costs.
+    async fn put_multipart(&self, key: &str, body: Vec<u8>) -> Result<(), VaultError> {
+        let mut create_request = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::Private);
+        if let Some(sse_c) = &self.sse_c {
+            create_request = create_request
+                .sse_customer_algorithm("AES256")
+                .sse_customer_key(&sse_c.key_base64)
+                .sse_customer_key_md5(&sse_c.key_md5_base64);
+        }
+        let upload = create_request.send().await?;
+        let upload_id = upload
+            .upload_id()
+            .ok_or(VaultError::S3NoContentsError)?
+            .to_string();
+
+        match self.upload_parts(key, &upload_id, body).await {
+            Ok(completed_parts) => {
+                self.client
+                    .complete_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .multipart_upload(
+                        CompletedMultipartUpload::builder()
+                            .set_parts(Some(completed_parts))
+                            .build(),
+                    )
+                    .send()
+                    .await?;
+                Ok(())
+            }
+            Err(err) => {
+                if let Err(abort_err) = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .send()
+                    .await
+                {
+                    eprintln!(
+                        "Failed to abort S3 multipart upload for '{key}': {}",
+                        VaultError::from(abort_err)
+                    );
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Upload each part of `body` for an in-progress multipart upload.
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        body: Vec<u8>,
+    ) -> Result<Vec<CompletedPart>, VaultError> {
+        let mut completed_parts = Vec::new();
+        for (index, chunk) in body.chunks(MULTIPART_PART_SIZE).enumerate() {
+            let part_number = i32::try_from(index + 1).unwrap_or(i32::MAX);
+            let mut request = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(chunk.to_vec()));
+            if let Some(sse_c) = &self.sse_c {
+                request = request
+                    .sse_customer_algorithm("AES256")
+                    .sse_customer_key(&sse_c.key_base64)
+                    .sse_customer_key_md5(&sse_c.key_md5_base64);
+            }
+            let output = request.send().await?;
+            completed_parts.push(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(output.e_tag().map(ToOwned::to_owned))
+                    .build(),
+            );
+        }
+        Ok(completed_parts)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Backend {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), VaultError> {
+        if body.len() > MULTIPART_THRESHOLD {
+            return self.put_multipart(key, body).await;
+        }
+
+        let mut request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::Private)
+            .body(ByteStream::from(body));
+        if let Some(sse_c) = &self.sse_c {
+            request = request
+                .sse_customer_algorithm("AES256")
+                .sse_customer_key(&sse_c.key_base64)
+                .sse_customer_key_md5(&sse_c.key_md5_base64);
+        }
+        request.send().await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, VaultError> {
+        let mut request = self.client.get_object().bucket(&self.bucket).key(key);
+        if let Some(sse_c) = &self.sse_c {
+            request = request
+                .sse_customer_algorithm("AES256")
+                .sse_customer_key(&sse_c.key_base64)
+                .sse_customer_key_md5(&sse_c.key_md5_base64);
+        }
+        let mut body = request.send().await?.body;
+
+        // Stream the response in chunks instead of buffering it with a
+        // single `collect()`, since AES-GCM still needs the whole
+        // ciphertext in memory to verify the tag before it can decrypt.
+        let mut buffer = Vec::new();
+        while let Some(chunk) = body
+            .try_next()
+            .await
+            .map_err(|_| VaultError::S3GetObjectBodyError)?
+        {
+            buffer.extend_from_slice(&chunk);
+        }
+        Ok(buffer)
+    }
+
+    async fn delete_many(&self, keys: &[String]) -> Result<(), VaultError> {
+        let identifiers = keys
+            .iter()
+            .map(|key| {
+                ObjectIdentifier::builder()
+                    .set_key(Some(key.clone()))
+                    .build()
+                    .map_err(VaultError::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.client
+            .delete_objects()
+            .bucket(&self.bucket)
+            .delete(Delete::builder().set_objects(Some(identifiers)).build()?)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_paged(
+        &self,
+        on_page: &mut (dyn FnMut(Vec<String>) + Send),
+    ) -> Result<(), VaultError> {
+        let mut continuation_token = None;
+
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await?;
+
+            on_page(
+                output
+                    .contents()
+                    .iter()
+                    .filter_map(|object| object.key().map(ToOwned::to_owned))
+                    .collect(),
+            );
+
+            if output.is_truncated() != Some(true) {
+                break;
+            }
+            continuation_token = output.next_continuation_token().map(ToOwned::to_owned);
+        }
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, VaultError> {
+        let mut request = self.client.head_object().bucket(&self.bucket).key(key);
+        if let Some(sse_c) = &self.sse_c {
+            request = request
+                .sse_customer_algorithm("AES256")
+                .sse_customer_key(&sse_c.key_base64)
+                .sse_customer_key_md5(&sse_c.key_md5_base64);
+        }
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_not_found() {
+                    // The object does not exist
+                    Ok(false)
+                } else {
+                    // Propagate other errors like networking or permissions
+                    Err(VaultError::S3HeadObjectError(service_error))
+                }
+            }
+        }
+    }
+
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, VaultError> {
+        let mut request = self.client.get_object().bucket(&self.bucket).key(key);
+        if let Some(sse_c) = &self.sse_c {
+            request = request
+                .sse_customer_algorithm("AES256")
+                .sse_customer_key(&sse_c.key_base64)
+                .sse_customer_key_md5(&sse_c.key_md5_base64);
+        }
+        let presigned = request
+            .presigned(PresigningConfig::expires_in(expires_in)?)
+            .await?;
+        Ok(presigned.uri().to_string())
+    }
+}
+
+/// `StorageBackend` backed by an in-memory `HashMap`, so the put/get/list/
+/// delete/exists operations of the trait can be unit-tested without a real
+/// S3 bucket or network access.
+///
+/// `Vault::new` always builds an [`S3Backend`], so this backend is not used
+/// by `Vault` itself: `Vault`'s store/lookup/delete round trips are not
+/// exercised through it.
+#[derive(Debug, Default)]
+pub struct InMemoryBackend {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryBackend {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for InMemoryBackend {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), VaultError> {
+        self.objects.lock().unwrap().insert(key.to_string(), body);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, VaultError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or(VaultError::S3NoContentsError)
+    }
+
+    async fn delete_many(&self, keys: &[String]) -> Result<(), VaultError> {
+        let mut objects = self.objects.lock().unwrap();
+        for key in keys {
+            objects.remove(key);
+        }
+        Ok(())
+    }
+
+    async fn list_paged(
+        &self,
+        on_page: &mut (dyn FnMut(Vec<String>) + Send),
+    ) -> Result<(), VaultError> {
+        on_page(self.objects.lock().unwrap().keys().cloned().collect());
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, VaultError> {
+        Ok(self.objects.lock().unwrap().contains_key(key))
+    }
+
+    async fn presign_get(&self, _key: &str, _expires_in: Duration) -> Result<String, VaultError> {
+        Err(VaultError::PresignError(
+            "in-memory backend has no presignable URLs".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn put_then_get_round_trips() {
+        let backend = InMemoryBackend::new();
+        backend.put("a.key", vec![1, 2, 3]).await.unwrap();
+        assert_eq!(backend.get("a.key").await.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn get_missing_key_errors() {
+        let backend = InMemoryBackend::new();
+        assert!(backend.get("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn exists_reflects_put_and_delete() {
+        let backend = InMemoryBackend::new();
+        assert!(!backend.exists("a.key").await.unwrap());
+        backend.put("a.key", vec![1]).await.unwrap();
+        assert!(backend.exists("a.key").await.unwrap());
+        backend
+            .delete_many(&["a.key".to_string()])
+            .await
+            .unwrap();
+        assert!(!backend.exists("a.key").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn delete_many_ignores_missing_keys() {
+        let backend = InMemoryBackend::new();
+        backend.put("a.key", vec![1]).await.unwrap();
+        backend
+            .delete_many(&["a.key".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert!(!backend.exists("a.key").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn list_reflects_prefixed_keys() {
+        let backend = InMemoryBackend::new();
+        backend.put("secrets/a.key", vec![1]).await.unwrap();
+        backend.put("secrets/b.key", vec![2]).await.unwrap();
+        backend.put("other/a.key", vec![3]).await.unwrap();
+
+        let mut keys = backend.list().await.unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["other/a.key".to_string(), "secrets/a.key".to_string(), "secrets/b.key".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn presign_get_is_unsupported_in_memory() {
+        let backend = InMemoryBackend::new();
+        backend.put("a.key", vec![1]).await.unwrap();
+        assert!(backend
+            .presign_get("a.key", Duration::from_secs(60))
+            .await
+            .is_err());
+    }
+}
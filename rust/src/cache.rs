@@ -0,0 +1,206 @@
+//! Opt-in on-disk cache for `lookup` and `list_all_keys`.
+//!
+//! Entries are keyed by a hash of the operation identity (the vault's
+//! region/bucket/stack plus the operation and key name) and stored as
+//! `captured_at` + raw bytes under a per-user cache directory, with a
+//! sibling lock file so concurrent `vault` invocations for the same key
+//! don't write a torn entry. The lock only covers writing an entry, not
+//! fetching it: concurrent invocations that miss the cache each fetch from
+//! AWS. Caching is always off unless a caller supplies a TTL, since these
+//! are secrets.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+use crate::errors::VaultError;
+use crate::get_env_variable;
+
+/// How long to wait between attempts to acquire an entry's lock file.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Stop waiting for another process's lock and write anyway, rather than
+/// blocking indefinitely.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Unix timestamp the entry was captured at.
+    captured_at: u64,
+    data: Vec<u8>,
+}
+
+/// Result of looking up an identity in the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Entry is younger than the TTL; use it as-is.
+    Fresh,
+    /// Entry is older than the TTL but still within the stale-while-revalidate
+    /// bound; use it, but the caller should refresh it in the background.
+    Stale,
+    /// No usable entry was found.
+    Miss,
+}
+
+/// On-disk cache of previously fetched `Value` bytes.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Use `VAULT_CACHE_DIR` if set, otherwise `$XDG_CACHE_HOME/nitor-vault`
+    /// (falling back to `$HOME/.cache/nitor-vault`).
+    #[must_use]
+    pub fn new() -> Self {
+        let dir = get_env_variable("VAULT_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(default_cache_dir);
+        Self { dir }
+    }
+
+    /// Look up `identity` in the cache, classifying it against `ttl` and the
+    /// stale-while-revalidate bound `stale`.
+    pub async fn get(
+        &self,
+        identity: &str,
+        ttl: Duration,
+        stale: Duration,
+    ) -> Result<(CacheStatus, Option<Vec<u8>>), VaultError> {
+        let Ok(bytes) = std::fs::read(self.entry_path(identity)) else {
+            return Ok((CacheStatus::Miss, None));
+        };
+        let entry: CacheEntry = serde_json::from_slice(&bytes)?;
+        let age = Duration::from_secs(now().saturating_sub(entry.captured_at));
+
+        if age < ttl {
+            Ok((CacheStatus::Fresh, Some(entry.data)))
+        } else if age < stale {
+            Ok((CacheStatus::Stale, Some(entry.data)))
+        } else {
+            Ok((CacheStatus::Miss, Some(entry.data)))
+        }
+    }
+
+    /// Write `data` as the cached value for `identity`, taking the entry's
+    /// lock first and writing via temp-file-then-rename so a reader never
+    /// observes a torn entry.
+    pub async fn set(&self, identity: &str, data: &[u8]) -> Result<(), VaultError> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| VaultError::FileReadError(self.dir.display().to_string(), e))?;
+
+        let path = self.entry_path(identity);
+        let lock_path = lock_path_for(&path);
+        let _lock = self.acquire_lock(&lock_path).await?;
+
+        let entry = CacheEntry {
+            captured_at: now(),
+            data: data.to_vec(),
+        };
+        let serialized = serde_json::to_vec(&entry)?;
+
+        let tmp_path = path.with_extension("tmp");
+        // Leftover from a crashed previous write; remove it so `create_new`
+        // below doesn't spuriously fail.
+        std::fs::remove_file(&tmp_path).ok();
+        write_owner_only(&tmp_path, &serialized)
+            .map_err(|e| VaultError::FileReadError(tmp_path.display().to_string(), e))?;
+        std::fs::rename(&tmp_path, &path)
+            .map_err(|e| VaultError::FileReadError(path.display().to_string(), e))?;
+
+        std::fs::remove_file(&lock_path).ok();
+        Ok(())
+    }
+
+    /// Remove every cached entry and lock file.
+    pub fn clear(&self) -> Result<(), VaultError> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(VaultError::FileReadError(self.dir.display().to_string(), e)),
+        }
+    }
+
+    fn entry_path(&self, identity: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        identity.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    /// Create `lock_path` exclusively, waiting for another writer to finish
+    /// if it already exists. Gives up and proceeds without the lock after
+    /// `LOCK_TIMEOUT`. Callers take the lock only after the value has been
+    /// fetched, so it serializes writers of an entry and does not prevent
+    /// duplicate AWS fetches.
+    async fn acquire_lock(&self, lock_path: &Path) -> Result<LockGuard, VaultError> {
+        let start = SystemTime::now();
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(lock_path)
+            {
+                Ok(_) => return Ok(LockGuard),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if start.elapsed().unwrap_or_default() > LOCK_TIMEOUT {
+                        return Ok(LockGuard);
+                    }
+                    sleep(LOCK_POLL_INTERVAL).await;
+                }
+                Err(e) => return Err(VaultError::FileReadError(lock_path.display().to_string(), e)),
+            }
+        }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marker returned while holding an entry's lock file; currently unused
+/// beyond scoping the lock's lifetime, since the lock file itself is removed
+/// explicitly once the write completes.
+struct LockGuard;
+
+fn lock_path_for(entry_path: &Path) -> PathBuf {
+    entry_path.with_extension("lock")
+}
+
+fn default_cache_dir() -> PathBuf {
+    let base = get_env_variable("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| get_env_variable("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(|| PathBuf::from(".cache"));
+    base.join("nitor-vault")
+}
+
+/// Write `data` to `path`, creating it with `0600` permissions up front so
+/// the plaintext secret is never briefly world-readable under the default
+/// umask the way a `write`-then-`chmod` sequence would leave it.
+#[cfg(unix)]
+fn write_owner_only(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_owner_only(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -3,12 +3,19 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use aws_sdk_cloudformation::types::StackStatus;
+use base64::Engine;
 use clap::Command;
 use clap_complete::Shell;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use tokio::time::Duration;
 
-use nitor_vault::{cloudformation, CreateStackResult, UpdateStackResult, Value, Vault};
+use nitor_vault::{
+    cloudformation, Cache, CacheStatus, CreateStackResult, LocalVault, PresignTargets,
+    UpdateStackResult, Value, Vault,
+};
+#[cfg(feature = "mount")]
+use nitor_vault::mount as vault_mount;
 
 static WAIT_ANIMATION_DURATION: Duration = Duration::from_millis(500);
 static QUIET_WAIT_DURATION: Duration = Duration::from_secs(1);
@@ -16,13 +23,28 @@ static CLEAR_LINE: &str = "\x1b[2K";
 static WAIT_DOTS: [&str; 4] = [".", "..", "...", ""];
 
 /// Initialize a new vault stack with Cloudformation and wait for creation to finish.
+#[allow(clippy::too_many_arguments)]
 pub async fn init_vault_stack(
     stack_name: Option<String>,
     region: Option<String>,
     bucket: Option<String>,
+    profile: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
     quiet: bool,
 ) -> Result<()> {
-    match Vault::init(stack_name, region, bucket).await? {
+    match Vault::init(
+        stack_name,
+        region,
+        bucket,
+        profile,
+        role_arn,
+        role_session_name,
+        role_external_id,
+    )
+    .await?
+    {
         CreateStackResult::Exists { data } => {
             if !quiet {
                 println!("Vault stack already initialized");
@@ -83,6 +105,7 @@ pub async fn update_vault_stack(vault: &Vault, quiet: bool) -> Result<()> {
 }
 
 /// Store a key-value pair.
+#[allow(clippy::too_many_arguments)]
 pub async fn store(
     vault: &Vault,
     key: Option<String>,
@@ -90,6 +113,8 @@ pub async fn store(
     file: Option<String>,
     value_argument: Option<String>,
     overwrite: bool,
+    compress: bool,
+    chunked: bool,
     quiet: bool,
 ) -> Result<()> {
     let key = {
@@ -112,7 +137,7 @@ pub async fn store(
         }
     };
 
-    let value = read_value(value_positional, value_argument, file)?;
+    let value = read_value(value_positional, value_argument, file).await?;
 
     if !overwrite
         && vault
@@ -126,9 +151,74 @@ pub async fn store(
         )
     }
 
-    Box::pin(vault.store(&key, value.as_bytes()))
+    if chunked {
+        Box::pin(vault.store_chunked(&key, value.as_bytes()))
+            .await
+            .with_context(|| format!("Failed to store key '{key}'").red())
+    } else {
+        Box::pin(vault.store(&key, value.as_bytes(), compress))
+            .await
+            .with_context(|| format!("Failed to store key '{key}'").red())
+    }
+}
+
+/// Re-encrypt a secret under a freshly generated data key, or all secrets if
+/// `key` is `None`, without changing its plaintext value.
+pub async fn rotate(vault: &Vault, key: Option<String>, new_key: Option<String>) -> Result<()> {
+    match key {
+        Some(key) => {
+            if key.trim().is_empty() {
+                anyhow::bail!(format!("Empty key '{key}'").red())
+            }
+            vault
+                .rotate(&key, new_key.as_deref())
+                .await
+                .with_context(|| format!("Failed to rotate key '{key}'").red())
+        }
+        None => {
+            let rotated = vault
+                .rotate_all(new_key.as_deref())
+                .await
+                .with_context(|| "Failed to rotate all keys".red())?;
+            println!("Rotated {} key(s)", rotated.len());
+            Ok(())
+        }
+    }
+}
+
+/// Print presigned GET URLs for a secret's S3 objects, so a downstream
+/// system without vault/AWS credentials can fetch them before `expires_in`
+/// elapses. `targets` selects which of the `.key`/`.aesgcm.encrypted`/
+/// `.meta` objects to sign; any left unselected are omitted from the output.
+pub async fn presign(
+    vault: &Vault,
+    key: &str,
+    expires_in: Duration,
+    targets: PresignTargets,
+) -> Result<()> {
+    if key.trim().is_empty() {
+        anyhow::bail!(format!("Empty key '{key}'").red())
+    }
+    if !(targets.key || targets.cipher || targets.meta) {
+        anyhow::bail!("At least one of --key-object, --cipher or --meta must be given".red())
+    }
+
+    let urls = vault
+        .presign(key, expires_in, targets)
         .await
-        .with_context(|| format!("Failed to store key '{key}'").red())
+        .with_context(|| format!("Failed to presign key '{key}'").red())?;
+
+    if let Some(url) = urls.key {
+        println!("key: {url}");
+    }
+    if let Some(url) = urls.cipher {
+        println!("cipher: {url}");
+    }
+    if let Some(url) = urls.meta {
+        println!("meta: {url}");
+    }
+
+    Ok(())
 }
 
 /// Delete key value.
@@ -143,25 +233,376 @@ pub async fn delete(vault: &Vault, key: &str) -> Result<()> {
 }
 
 /// Get key value.
-pub async fn lookup(vault: &Vault, key: &str, outfile: Option<String>) -> Result<()> {
+///
+/// If `cache_ttl` is given, the result is served from the on-disk cache when
+/// fresh enough instead of doing a full S3 GET + KMS decrypt round trip.
+pub async fn lookup(
+    vault: &Vault,
+    key: &str,
+    outfile: Option<String>,
+    cache_ttl: Option<Duration>,
+    refresh_cache: bool,
+) -> Result<()> {
     if key.trim().is_empty() {
         anyhow::bail!(format!("Empty key '{key}'").red())
     }
 
-    let result = Box::pin(vault.lookup(key))
+    let result = match cache_ttl {
+        Some(ttl) => cached_lookup(vault, key, ttl, refresh_cache).await?,
+        None => Box::pin(vault.lookup(key))
+            .await
+            .with_context(|| format!("Failed to look up key '{key}'").red())?,
+    };
+
+    match resolve_output_file_path(outfile).await? {
+        Some(path) => result.output_to_file_async(&path).await?,
+        None => result.output_to_stdout_async().await?,
+    };
+
+    Ok(())
+}
+
+/// List all available keys.
+///
+/// If `cache_ttl` is given, the list is served from the on-disk cache when
+/// fresh enough instead of doing a full S3 listing. Otherwise names are
+/// printed page by page as they arrive, so a vault with more than one page
+/// of objects starts rendering before the full listing completes.
+pub async fn list_all_keys(
+    vault: &Vault,
+    cache_ttl: Option<Duration>,
+    refresh_cache: bool,
+) -> Result<()> {
+    match cache_ttl {
+        Some(ttl) => {
+            let list = cached_all(vault, ttl, refresh_cache).await?;
+            if !list.is_empty() {
+                println!("{}", list.join("\n"));
+            }
+        }
+        None => {
+            vault
+                .all_paged(&mut |page| {
+                    if !page.is_empty() {
+                        println!("{}", page.join("\n"));
+                    }
+                })
+                .await
+                .with_context(|| "Failed to list all keys".red())?;
+        }
+    }
+    Ok(())
+}
+
+/// Wipe every entry in the on-disk `lookup`/`list_all_keys` cache.
+pub async fn cache_clear() -> Result<()> {
+    Cache::new()
+        .clear()
+        .with_context(|| "Failed to clear cache".red())
+}
+
+/// Mount `vault` as a read-only FUSE filesystem at `mountpoint`, blocking
+/// until it is unmounted.
+#[cfg(feature = "mount")]
+pub async fn mount(vault: Vault, mountpoint: &str) -> Result<()> {
+    let mountpoint = Path::new(mountpoint);
+    tokio::task::spawn_blocking({
+        let mountpoint = mountpoint.to_path_buf();
+        move || vault_mount(vault, &mountpoint)
+    })
+    .await
+    .with_context(|| "Mount task panicked".red())?
+    .with_context(|| format!("Failed to mount vault at '{}'", mountpoint.display()).red())
+}
+
+/// An entry older than `ttl` but younger than this multiple of `ttl` is
+/// still served immediately (stale-while-revalidate) instead of falling
+/// back to a full fetch.
+const STALE_MULTIPLIER: u32 = 4;
+
+/// Look up `key`, consulting the on-disk cache first.
+///
+/// A fresh entry is returned as-is. A stale entry is returned immediately
+/// and then refreshed before this call returns: unlike a long-running
+/// daemon, a one-shot `vault` invocation has no later point at which a
+/// truly backgrounded refresh could still run, so "background" here means
+/// "after the stale value has already been handed back to the caller".
+async fn cached_lookup(vault: &Vault, key: &str, ttl: Duration, refresh: bool) -> Result<Value> {
+    let cache = Cache::new();
+    let identity = format!("{vault}\nlookup {key}");
+
+    if !refresh {
+        let (status, cached) = cache
+            .get(&identity, ttl, ttl.saturating_mul(STALE_MULTIPLIER))
+            .await
+            .with_context(|| "Failed to read lookup cache".red())?;
+        match (status, cached) {
+            (CacheStatus::Fresh, Some(bytes)) => return Ok(Value::new(bytes)),
+            (CacheStatus::Stale, Some(bytes)) => {
+                let stale_value = Value::new(bytes);
+                refresh_lookup_cache(vault, key, &cache, &identity).await?;
+                return Ok(stale_value);
+            }
+            (CacheStatus::Miss, _) | (_, None) => {}
+        }
+    }
+
+    refresh_lookup_cache(vault, key, &cache, &identity).await
+}
+
+async fn refresh_lookup_cache(
+    vault: &Vault,
+    key: &str,
+    cache: &Cache,
+    identity: &str,
+) -> Result<Value> {
+    let value = Box::pin(vault.lookup(key))
         .await
         .with_context(|| format!("Failed to look up key '{key}'").red())?;
+    cache
+        .set(identity, value.as_bytes())
+        .await
+        .with_context(|| "Failed to write lookup cache".red())?;
+    Ok(value)
+}
+
+/// List all keys, consulting the on-disk cache first. See [`cached_lookup`]
+/// for the fresh/stale/miss handling.
+async fn cached_all(vault: &Vault, ttl: Duration, refresh: bool) -> Result<Vec<String>> {
+    let cache = Cache::new();
+    let identity = format!("{vault}\nall");
 
-    match resolve_output_file_path(outfile)? {
-        Some(path) => result.output_to_file(&path)?,
-        None => result.output_to_stdout()?,
+    if !refresh {
+        let (status, cached) = cache
+            .get(&identity, ttl, ttl.saturating_mul(STALE_MULTIPLIER))
+            .await
+            .with_context(|| "Failed to read list cache".red())?;
+        if let Some(bytes) = cached {
+            let list: Vec<String> = serde_json::from_slice(&bytes)
+                .with_context(|| "Failed to parse cached key list".red())?;
+            match status {
+                CacheStatus::Fresh => return Ok(list),
+                CacheStatus::Stale => {
+                    refresh_all_cache(vault, &cache, &identity).await?;
+                    return Ok(list);
+                }
+                CacheStatus::Miss => {}
+            }
+        }
+    }
+
+    refresh_all_cache(vault, &cache, &identity).await
+}
+
+async fn refresh_all_cache(vault: &Vault, cache: &Cache, identity: &str) -> Result<Vec<String>> {
+    let list = vault
+        .all()
+        .await
+        .with_context(|| "Failed to list all keys".red())?;
+    let bytes =
+        serde_json::to_vec(&list).with_context(|| "Failed to serialize key list".red())?;
+    cache
+        .set(identity, &bytes)
+        .await
+        .with_context(|| "Failed to write list cache".red())?;
+    Ok(list)
+}
+
+/// Check if key exists.
+pub async fn exists(vault: &Vault, key: &str, quiet: bool) -> Result<bool> {
+    if key.trim().is_empty() {
+        anyhow::bail!(format!("Empty key: '{key}'").red())
+    }
+
+    let exists = vault
+        .exists(key)
+        .await
+        .with_context(|| format!("Failed to check if key '{key}' exists").red())?;
+
+    if !quiet {
+        if exists {
+            println!("key '{key}' exists");
+        } else {
+            println!("{}", format!("key '{key}' doesn't exist").red());
+        }
+    }
+
+    Ok(exists)
+}
+
+/// One secret in a `vault export` archive.
+///
+/// `value` always holds the secret's raw bytes base64-encoded, regardless
+/// of whether it's UTF-8 or binary, so the archive round-trips as plain
+/// JSON without guessing at a value's encoding on import — guessing would
+/// corrupt any UTF-8 secret that happens to itself be valid base64 (an AWS
+/// secret access key, for instance).
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveEntry {
+    key: String,
+    value: String,
+}
+
+/// Serialize every secret in the vault into a single JSON archive,
+/// suitable for `vault import`ing into another vault stack, account, or region.
+pub async fn export(vault: &Vault, file: Option<String>) -> Result<()> {
+    let keys = vault
+        .all()
+        .await
+        .with_context(|| "Failed to list all keys".red())?;
+
+    let mut entries = Vec::with_capacity(keys.len());
+    for key in keys {
+        let value = Box::pin(vault.lookup(&key))
+            .await
+            .with_context(|| format!("Failed to look up key '{key}'").red())?;
+        let value = base64::engine::general_purpose::STANDARD.encode(value.as_bytes());
+        entries.push(ArchiveEntry { key, value });
+    }
+
+    let archive = serde_json::to_vec_pretty(&entries)
+        .with_context(|| "Failed to serialize vault archive".red())?;
+    let archive = Value::Binary(archive);
+
+    match file.as_deref() {
+        Some("-") | None => archive.output_to_stdout_async().await?,
+        Some(path) => {
+            let path = resolve_output_file_path(Some(path.to_string()))
+                .await?
+                .expect("outfile path was Some");
+            archive.output_to_file_async(&path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Store every secret from a `vault export` archive, honoring `--overwrite`
+/// the same way `vault store` does for each key. Keys that already exist
+/// and weren't overwritten are reported as conflicts rather than failing
+/// the whole import.
+pub async fn import(vault: &Vault, file: String, overwrite: bool) -> Result<()> {
+    let data = read_value(None, None, Some(file)).await?;
+    let entries: Vec<ArchiveEntry> = serde_json::from_slice(data.as_bytes())
+        .with_context(|| "Failed to parse vault archive".red())?;
+
+    let mut conflicts = Vec::new();
+    for entry in entries {
+        if !overwrite
+            && vault.exists(&entry.key).await.with_context(|| {
+                format!("Failed to check if key '{}' exists", entry.key).red()
+            })?
+        {
+            conflicts.push(entry.key);
+            continue;
+        }
+
+        let value = base64::engine::general_purpose::STANDARD
+            .decode(&entry.value)
+            .with_context(|| format!("Failed to decode archived value for key '{}'", entry.key).red())?;
+        Box::pin(vault.store(&entry.key, &value, false))
+            .await
+            .with_context(|| format!("Failed to store key '{}'", entry.key).red())?;
+    }
+
+    if !conflicts.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "Skipped {} existing key(s), use {} to replace them: {}",
+                conflicts.len(),
+                "--overwrite".yellow().bold(),
+                conflicts.join(", ")
+            )
+            .red()
+        );
+    }
+
+    Ok(())
+}
+
+/// Store a key-value pair in an offline local vault file.
+pub async fn local_store(
+    vault: &LocalVault,
+    key: Option<String>,
+    value_positional: Option<String>,
+    file: Option<String>,
+    value_argument: Option<String>,
+    overwrite: bool,
+    quiet: bool,
+) -> Result<()> {
+    let key = {
+        if let Some(key) = key {
+            key
+        } else if let Some(file_name) = &file {
+            if file_name == "-" {
+                anyhow::bail!("Key cannot be empty when reading from stdin".red())
+            }
+            let key = get_filename_from_path(file_name)?;
+            if !quiet {
+                println!("Using filename as key: '{key}'");
+            }
+            key
+        } else {
+            anyhow::bail!(
+                "Empty key and no {} flag provided, provide at least one of these",
+                "--file".yellow().bold()
+            )
+        }
+    };
+
+    let value = read_value(value_positional, value_argument, file).await?;
+
+    if !overwrite
+        && vault
+            .exists(&key)
+            .await
+            .with_context(|| format!("Failed to check if key '{key}' exists").red())?
+    {
+        anyhow::bail!(
+            "Key already exists and no {} flag provided for overwriting",
+            "-w".yellow().bold()
+        )
+    }
+
+    vault
+        .store(&key, value.as_bytes())
+        .await
+        .with_context(|| format!("Failed to store key '{key}'").red())
+}
+
+/// Delete key value from an offline local vault file.
+pub async fn local_delete(vault: &LocalVault, key: &str) -> Result<()> {
+    if key.trim().is_empty() {
+        anyhow::bail!(format!("Empty key '{key}'").red())
+    }
+    vault
+        .delete(key)
+        .await
+        .with_context(|| format!("Failed to delete key '{key}'").red())
+}
+
+/// Get key value from an offline local vault file.
+pub async fn local_lookup(vault: &LocalVault, key: &str, outfile: Option<String>) -> Result<()> {
+    if key.trim().is_empty() {
+        anyhow::bail!(format!("Empty key '{key}'").red())
+    }
+
+    let result = vault
+        .lookup(key)
+        .await
+        .with_context(|| format!("Failed to look up key '{key}'").red())?;
+
+    match resolve_output_file_path(outfile).await? {
+        Some(path) => result.output_to_file_async(&path).await?,
+        None => result.output_to_stdout_async().await?,
     };
 
     Ok(())
 }
 
-/// List all available keys.
-pub async fn list_all_keys(vault: &Vault) -> Result<()> {
+/// List all available keys in an offline local vault file.
+pub async fn local_list_all(vault: &LocalVault) -> Result<()> {
     vault
         .all()
         .await
@@ -173,8 +614,8 @@ pub async fn list_all_keys(vault: &Vault) -> Result<()> {
         })
 }
 
-/// Check if key exists.
-pub async fn exists(vault: &Vault, key: &str, quiet: bool) -> Result<bool> {
+/// Check if key exists in an offline local vault file.
+pub async fn local_exists(vault: &LocalVault, key: &str, quiet: bool) -> Result<bool> {
     if key.trim().is_empty() {
         anyhow::bail!(format!("Empty key: '{key}'").red())
     }
@@ -205,13 +646,13 @@ pub async fn encrypt(
     value_argument: Option<String>,
     outfile: Option<String>,
 ) -> Result<()> {
-    let data = read_value(value_positional, value_argument, file)?;
+    let data = read_value(value_positional, value_argument, file).await?;
     let bytes = vault.direct_encrypt(data.as_bytes()).await?;
     let value = Value::new(bytes).encode_base64();
 
-    match resolve_output_file_path(outfile)? {
-        Some(path) => value.output_to_file(&path)?,
-        None => value.output_to_stdout()?,
+    match resolve_output_file_path(outfile).await? {
+        Some(path) => value.output_to_file_async(&path).await?,
+        None => value.output_to_stdout_async().await?,
     };
 
     Ok(())
@@ -227,13 +668,13 @@ pub async fn decrypt(
     value_argument: Option<String>,
     outfile: Option<String>,
 ) -> Result<()> {
-    let data = read_value(value_positional, value_argument, file)?.decode_base64();
+    let data = read_value(value_positional, value_argument, file).await?.decode_base64();
     let bytes = vault.direct_decrypt(data.as_bytes()).await?;
     let value = Value::new(bytes);
 
-    match resolve_output_file_path(outfile)? {
-        Some(path) => value.output_to_file(&path)?,
-        None => value.output_to_stdout()?,
+    match resolve_output_file_path(outfile).await? {
+        Some(path) => value.output_to_file_async(&path).await?,
+        None => value.output_to_stdout_async().await?,
     };
 
     Ok(())
@@ -386,13 +827,13 @@ fn get_filename_from_path(path: &str) -> Result<String> {
 /// Resolves an optional output file path and creates all directories if necessary.
 /// Returns `Some(PathBuf)` if the file path is valid,
 /// or `None` if a file path was not provided.
-fn resolve_output_file_path(outfile: Option<String>) -> Result<Option<PathBuf>> {
+async fn resolve_output_file_path(outfile: Option<String>) -> Result<Option<PathBuf>> {
     if let Some(output) = outfile {
         let path = PathBuf::from(output);
 
         // Ensure all parent directories exist
         if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).with_context(|| {
+            tokio::fs::create_dir_all(parent).await.with_context(|| {
                 format!("Failed to create directories for '{}'", parent.display())
             })?;
         }
@@ -403,21 +844,24 @@ fn resolve_output_file_path(outfile: Option<String>) -> Result<Option<PathBuf>>
 }
 
 /// Read value depending on given CLI arguments.
-fn read_value(
+///
+/// Reads the file or stdin via `tokio::fs`/`tokio::io` so a large secret
+/// doesn't block the runtime thread for the duration of the read.
+async fn read_value(
     value_positional: Option<String>,
     value_argument: Option<String>,
     file: Option<String>,
 ) -> Result<Value> {
     Ok(if let Some(value) = value_positional.or(value_argument) {
         if value == "-" {
-            Value::from_stdin()?
+            Value::from_stdin_async().await?
         } else {
             Value::Utf8(value)
         }
     } else if let Some(path) = file {
         match path.as_str() {
-            "-" => Value::from_stdin()?,
-            _ => Value::from_path(path)?,
+            "-" => Value::from_stdin_async().await?,
+            _ => Value::from_path_async(path).await?,
         }
     } else {
         anyhow::bail!("No value or filename provided".red())
@@ -479,3 +923,28 @@ fn get_shell_completion_dir(shell: Shell) -> Result<PathBuf> {
     std::fs::create_dir_all(&user_dir)?;
     Ok(user_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_entry_round_trips_utf8_value_that_is_valid_base64() {
+        // "YWJj" is a plausible secret value that is *also* valid base64
+        // (it decodes to "abc"). Guessing at decode time, instead of
+        // always base64-decoding the archived `value`, would silently
+        // corrupt it.
+        let original = "YWJj";
+        let encoded = base64::engine::general_purpose::STANDARD.encode(original.as_bytes());
+        let entry = ArchiveEntry {
+            key: "mykey".to_string(),
+            value: encoded,
+        };
+
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&entry.value)
+            .unwrap();
+
+        assert_eq!(decoded, original.as_bytes());
+    }
+}
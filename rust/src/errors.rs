@@ -15,12 +15,17 @@ use aws_sdk_kms::operation::decrypt::DecryptError;
 use aws_sdk_kms::operation::encrypt::EncryptError;
 use aws_sdk_kms::operation::generate_data_key::GenerateDataKeyError;
 use aws_sdk_s3::error::BuildError;
+use aws_sdk_s3::operation::abort_multipart_upload::AbortMultipartUploadError;
+use aws_sdk_s3::operation::complete_multipart_upload::CompleteMultipartUploadError;
+use aws_sdk_s3::operation::create_multipart_upload::CreateMultipartUploadError;
 use aws_sdk_s3::operation::delete_object::DeleteObjectError;
 use aws_sdk_s3::operation::delete_objects::DeleteObjectsError;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use aws_sdk_s3::operation::head_object::HeadObjectError;
 use aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Error;
 use aws_sdk_s3::operation::put_object::PutObjectError;
+use aws_sdk_s3::operation::upload_part::UploadPartError;
+use aws_sdk_s3::presigning::PresigningConfigError;
 use aws_sdk_sts::operation::get_caller_identity::GetCallerIdentityError;
 
 use thiserror::Error;
@@ -75,6 +80,16 @@ pub enum VaultError {
     S3DeleteObjectsError(Box<SdkError<DeleteObjectsError>>),
     #[error("No contents found from S3")]
     S3NoContentsError,
+    #[error("Failed to create S3 multipart upload")]
+    S3CreateMultipartError(Box<SdkError<CreateMultipartUploadError>>),
+    #[error("Failed to upload S3 multipart part")]
+    S3UploadPartError(Box<SdkError<UploadPartError>>),
+    #[error("Failed to complete S3 multipart upload")]
+    S3CompleteMultipartError(Box<SdkError<CompleteMultipartUploadError>>),
+    #[error("Failed to abort S3 multipart upload")]
+    S3AbortMultipartError(Box<SdkError<AbortMultipartUploadError>>),
+    #[error("Failed to generate presigned URL: {0}")]
+    PresignError(String),
     #[error("Failed getting region")]
     NoRegionError,
     #[error("Failed parsing Nonce from base64")]
@@ -93,6 +108,10 @@ pub enum VaultError {
     MissingAccountIdError,
     #[error("Failed to get called ID: {0}")]
     CallerIdError(Box<SdkError<GetCallerIdentityError>>),
+    #[error("Failed to assume IAM role via STS: {0}")]
+    AssumeRoleError(aws_credential_types::provider::error::CredentialsError),
+    #[error("Failed to resolve web-identity credentials: {0}")]
+    WebIdentityError(aws_credential_types::provider::error::CredentialsError),
     #[error("Failed to create stack: {0}")]
     CreateStackError(Box<SdkError<CreateStackError>>),
     #[error("Failed to get stack ID for new vault stack")]
@@ -107,6 +126,27 @@ pub enum VaultError {
     ListVaultStacksError(Box<SdkError<ListStacksError>>),
     #[error("Failed to delete stack: {0}")]
     DeleteVaultStackError(Box<SdkError<DeleteStackError>>),
+    #[error("Key does not exist in local vault: '{0}'")]
+    LocalVaultKeyNotFoundError(String),
+    #[error("Local vault file is not a recognized vault file, or uses an unsupported version")]
+    LocalVaultCorruptHeaderError,
+    #[error("Failed to decrypt local vault file, passphrase may be incorrect")]
+    LocalVaultDecryptError,
+    #[error("Failed to derive key from passphrase: {0}")]
+    LocalVaultKeyDerivationError(String),
+    #[error("Failed to compress value with zstd: {0}")]
+    CompressionError(io::Error),
+    #[error("Failed to decompress value with zstd: {0}")]
+    DecompressionError(io::Error),
+    #[error("Secret was encrypted with a passphrase-derived key; pass --key-passphrase or set VAULT_KEY_PASSPHRASE")]
+    PassphraseRequiredError,
+    #[error("--chunked storage always wraps its shared chunk data key with KMS; it isn't supported together with --key-passphrase")]
+    ChunkedStoragePassphraseUnsupportedError,
+    #[error("SSE-C key must be 32 raw bytes (base64-encoded), got {0}")]
+    InvalidSseCustomerKeyLengthError(usize),
+    #[cfg(feature = "mount")]
+    #[error("Failed to mount vault filesystem: {0}")]
+    MountError(io::Error),
 }
 
 impl From<SdkError<DescribeStacksError>> for VaultError {
@@ -163,6 +203,36 @@ impl From<SdkError<DeleteObjectsError>> for VaultError {
     }
 }
 
+impl From<SdkError<CreateMultipartUploadError>> for VaultError {
+    fn from(err: SdkError<CreateMultipartUploadError>) -> Self {
+        Self::S3CreateMultipartError(Box::new(err))
+    }
+}
+
+impl From<SdkError<UploadPartError>> for VaultError {
+    fn from(err: SdkError<UploadPartError>) -> Self {
+        Self::S3UploadPartError(Box::new(err))
+    }
+}
+
+impl From<SdkError<CompleteMultipartUploadError>> for VaultError {
+    fn from(err: SdkError<CompleteMultipartUploadError>) -> Self {
+        Self::S3CompleteMultipartError(Box::new(err))
+    }
+}
+
+impl From<SdkError<AbortMultipartUploadError>> for VaultError {
+    fn from(err: SdkError<AbortMultipartUploadError>) -> Self {
+        Self::S3AbortMultipartError(Box::new(err))
+    }
+}
+
+impl From<PresigningConfigError> for VaultError {
+    fn from(err: PresigningConfigError) -> Self {
+        Self::PresignError(err.to_string())
+    }
+}
+
 impl From<SdkError<UpdateStackError>> for VaultError {
     fn from(err: SdkError<UpdateStackError>) -> Self {
         Self::UpdateStackError(Box::new(err))
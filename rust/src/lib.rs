@@ -1,8 +1,13 @@
 pub mod args;
+pub mod backend;
 pub mod cli;
 pub mod cloudformation;
 pub mod errors;
 
+mod cache;
+mod local;
+#[cfg(feature = "mount")]
+mod mount;
 mod template;
 mod value;
 mod vault;
@@ -10,12 +15,19 @@ mod vault;
 // Expose `Vault` and `Value` so they can be used as if they were defined here
 pub use crate::args::run_cli;
 pub use crate::args::run_cli_with_args;
+pub use crate::cache::{Cache, CacheStatus};
+pub use crate::local::LocalVault;
+#[cfg(feature = "mount")]
+pub use crate::mount::mount;
 pub use crate::value::Value;
 pub use crate::vault::Vault;
 
+use aws_config::imds::credentials::ImdsCredentialsProvider;
 use aws_config::meta::region::RegionProviderChain;
+use aws_config::sts::AssumeRoleProvider;
+use aws_config::web_identity_token::WebIdentityTokenCredentialsProvider;
 use aws_config::{Region, SdkConfig};
-use aws_sdk_s3::types::ObjectIdentifier;
+use aws_credential_types::provider::ProvideCredentials;
 use aws_sdk_sts::config::Credentials;
 use base64::Engine;
 use serde::{Deserialize, Serialize};
@@ -62,6 +74,49 @@ pub struct VaultConfig {
     pub profile: Option<String>,
     pub iam_id: Option<String>,
     pub iam_secret: Option<String>,
+    /// Override the S3 endpoint URL, for S3-compatible stores like MinIO,
+    /// Garage or Ceph instead of AWS S3.
+    pub endpoint_url: Option<String>,
+    /// Force S3 path-style addressing (`endpoint/bucket/key` instead of
+    /// `bucket.endpoint/key`), which most S3-compatible stores require
+    /// since they don't support virtual-hosted-style requests. Only takes
+    /// effect when `endpoint_url` is also set.
+    pub force_path_style: bool,
+    /// Path to an OIDC web-identity token file (e.g. the one EKS/IRSA
+    /// mounts into the pod), for federated credentials. Requires
+    /// `web_identity_role_arn`.
+    pub web_identity_token_file: Option<String>,
+    /// IAM role ARN to assume with the web-identity token.
+    pub web_identity_role_arn: Option<String>,
+    /// Force the EC2/ECS instance-metadata-service credentials provider
+    /// instead of the default provider chain.
+    pub use_imds: bool,
+    /// IAM role ARN to assume via STS on top of whichever base credentials
+    /// were otherwise resolved.
+    pub assume_role_arn: Option<String>,
+    /// Session name for the `assume_role_arn` call.
+    pub assume_role_session_name: Option<String>,
+    /// External ID for the `assume_role_arn` call.
+    pub assume_role_external_id: Option<String>,
+}
+
+/// Which of a secret's S3 objects to mint a presigned URL for, passed to
+/// [`Vault::presign`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PresignTargets {
+    pub key: bool,
+    pub cipher: bool,
+    pub meta: bool,
+}
+
+/// Presigned GET URLs for a secret's `.key`/`.aesgcm.encrypted`/`.meta`
+/// objects, returned by [`Vault::presign`]. Each field is `None` when the
+/// matching [`PresignTargets`] flag wasn't set.
+#[derive(Debug, Clone, Default)]
+pub struct PresignedSecretUrls {
+    pub key: Option<String>,
+    pub cipher: Option<String>,
+    pub meta: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,8 +130,76 @@ pub(crate) struct EncryptObject {
 pub(crate) struct Meta {
     alg: String,
     nonce: String,
+    /// Compression algorithm applied before encryption.
+    /// `0` (the default for older entries without this field) means
+    /// the value was stored uncompressed.
+    #[serde(default)]
+    compression: u8,
+    /// Length of the value before compression, used to preallocate the
+    /// decompression buffer. Unused when `compression` is `0`.
+    #[serde(default)]
+    original_len: u64,
+    /// `true` if the encrypted payload is a [`ChunkManifest`] rather than
+    /// the secret's own value, i.e. it was stored with `--chunked`.
+    #[serde(default)]
+    chunked: bool,
+    /// How the `.key` object is wrapped: `KEY_WRAP_KMS` (the default, for
+    /// entries predating this field) or `KEY_WRAP_ARGON2`. See
+    /// [`Argon2KeyWrapParams`].
+    #[serde(default)]
+    key_wrap: u8,
+    /// Present when `key_wrap` is `KEY_WRAP_ARGON2`.
+    #[serde(default)]
+    argon2: Option<Argon2KeyWrapParams>,
+}
+
+/// `Meta.key_wrap`: the `.key` object holds a KMS `ciphertext_blob`.
+pub(crate) const KEY_WRAP_KMS: u8 = 0;
+/// `Meta.key_wrap`: the `.key` object holds a data key AES-GCM-wrapped
+/// under an Argon2id-derived passphrase key, see [`Argon2KeyWrapParams`].
+pub(crate) const KEY_WRAP_ARGON2: u8 = 1;
+
+/// Parameters needed to re-derive the Argon2id key that wraps a secret's
+/// data key, when `Vault` is configured with a passphrase instead of KMS.
+/// Stored alongside the regular nonce so `lookup` can unwrap the `.key`
+/// object without anything beyond the passphrase itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Argon2KeyWrapParams {
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// Nonce used to AES-GCM-wrap the data key under the derived key.
+    nonce: String,
 }
 
+impl Argon2KeyWrapParams {
+    #[must_use]
+    fn new(salt: String, memory_kib: u32, iterations: u32, parallelism: u32, nonce: String) -> Self {
+        Self {
+            salt,
+            memory_kib,
+            iterations,
+            parallelism,
+            nonce,
+        }
+    }
+}
+
+/// Ordered list of content-addressed chunk digests making up a secret
+/// stored with `--chunked`, plus the total plaintext length needed to
+/// preallocate the reassembly buffer.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ChunkManifest {
+    pub(crate) chunks: Vec<String>,
+    pub(crate) total_len: u64,
+}
+
+/// No compression was applied; `store` wrote the plaintext length as-is.
+pub(crate) const COMPRESSION_NONE: u8 = 0;
+/// Value was compressed with zstd before encryption.
+pub(crate) const COMPRESSION_ZSTD: u8 = 1;
+
 /// S3 object identifier names for a single value.
 #[derive(Debug, Clone)]
 pub(crate) struct S3DataKeys {
@@ -87,17 +210,46 @@ pub(crate) struct S3DataKeys {
 
 impl Meta {
     #[must_use]
-    fn new(algorithm: &str, nonce: &[u8]) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        algorithm: &str,
+        nonce: &[u8],
+        compression: u8,
+        original_len: u64,
+        chunked: bool,
+        key_wrap: u8,
+        argon2: Option<Argon2KeyWrapParams>,
+    ) -> Self {
         Self {
             alg: algorithm.to_owned(),
             nonce: base64::engine::general_purpose::STANDARD.encode(nonce),
+            compression,
+            original_len,
+            chunked,
+            key_wrap,
+            argon2,
         }
     }
 
     /// Shorthand to initialize new Meta with AES-GCM algorithm.
     #[must_use]
-    fn aesgcm(nonce: &[u8]) -> Self {
-        Self::new("AESGCM", nonce)
+    fn aesgcm(
+        nonce: &[u8],
+        compression: u8,
+        original_len: u64,
+        chunked: bool,
+        key_wrap: u8,
+        argon2: Option<Argon2KeyWrapParams>,
+    ) -> Self {
+        Self::new(
+            "AESGCM",
+            nonce,
+            compression,
+            original_len,
+            chunked,
+            key_wrap,
+            argon2,
+        )
     }
 
     /// Serialize Meta to JSON string.
@@ -119,19 +271,6 @@ impl S3DataKeys {
     const fn as_array(&self) -> [&str; 3] {
         [self.key.as_str(), self.cipher.as_str(), self.meta.as_str()]
     }
-
-    /// Convert keys to S3 object identifiers.
-    fn to_object_identifiers(&self) -> Result<Vec<ObjectIdentifier>, VaultError> {
-        self.as_array()
-            .iter()
-            .map(|key| {
-                ObjectIdentifier::builder()
-                    .set_key(Some((*key).to_string()))
-                    .build()
-                    .map_err(VaultError::from)
-            })
-            .collect()
-    }
 }
 
 /// Return possible env variable value as Option.
@@ -143,51 +282,187 @@ pub fn get_env_variable(name: &str) -> Option<String> {
 
 /// Get AWS SDK config from optional arguments.
 ///
-/// Uses the following priority:
-/// 1. Use `id` and `secret` if provided.
-/// 2. Use the specified profile name if available.
-/// 3. Fallback to environment variables and defaults.
-#[must_use]
+/// Resolves a base credentials provider with the following priority:
+/// 1. Use `iam_id`/`iam_secret` if both are provided.
+/// 2. Use `web_identity_token_file`/`web_identity_role_arn` if both are
+///    provided, for EKS/IRSA and other OIDC federation.
+/// 3. Force the EC2/ECS instance-metadata-service provider if `use_imds`.
+/// 4. Use the specified profile name if available.
+/// 5. Fallback to environment variables and defaults.
+///
+/// If `assume_role_arn` is then set, those base credentials are used to
+/// assume that role via STS (`assume_role_session_name`/
+/// `assume_role_external_id` are passed through to the `AssumeRole` call),
+/// and the resulting temporary credentials replace them. Either way, the
+/// SDK transparently refreshes credentials shortly before they expire.
+///
+/// `endpoint_url`, when given, overrides the endpoint used by clients built
+/// from the returned config, for S3-compatible stores like MinIO or Garage.
 pub async fn resolve_aws_config_from_args(
     region: Option<String>,
     profile: Option<String>,
     iam_id: Option<String>,
     iam_secret: Option<String>,
-) -> SdkConfig {
-    if let (Some(id), Some(secret)) = (iam_id, iam_secret) {
-        get_aws_config_from_credentials(&id, &secret, region).await
+    endpoint_url: Option<String>,
+    web_identity_token_file: Option<String>,
+    web_identity_role_arn: Option<String>,
+    use_imds: bool,
+    assume_role_arn: Option<String>,
+    assume_role_session_name: Option<String>,
+    assume_role_external_id: Option<String>,
+) -> Result<SdkConfig, VaultError> {
+    let base_config = if let (Some(id), Some(secret)) = (iam_id, iam_secret) {
+        get_aws_config_from_credentials(&id, &secret, region.clone(), endpoint_url.clone()).await
+    } else if let (Some(token_file), Some(role_arn)) =
+        (web_identity_token_file, web_identity_role_arn)
+    {
+        get_aws_config_from_web_identity(
+            &token_file,
+            &role_arn,
+            region.clone(),
+            endpoint_url.clone(),
+        )
+        .await?
+    } else if use_imds {
+        get_aws_config_from_imds(region.clone(), endpoint_url.clone()).await
     } else {
-        get_aws_config(region, profile).await
+        get_aws_config(region.clone(), profile, endpoint_url.clone()).await
+    };
+
+    match assume_role_arn {
+        Some(role_arn) => {
+            get_aws_config_from_assumed_role(
+                &base_config,
+                &role_arn,
+                assume_role_session_name,
+                assume_role_external_id,
+                region,
+                endpoint_url,
+            )
+            .await
+        }
+        None => Ok(base_config),
     }
 }
 
-/// Return AWS SDK config with optional region name to use.
+/// Return AWS SDK config with optional region name and endpoint to use.
 #[inline]
 #[must_use]
-pub async fn get_aws_config(region: Option<String>, profile: Option<String>) -> SdkConfig {
-    profile
+pub async fn get_aws_config(
+    region: Option<String>,
+    profile: Option<String>,
+    endpoint_url: Option<String>,
+) -> SdkConfig {
+    let loader = profile
         .map_or_else(aws_config::from_env, |profile| {
             aws_config::from_env().profile_name(profile)
         })
-        .region(get_region_provider(region))
-        .load()
-        .await
+        .region(get_region_provider(region));
+    match endpoint_url {
+        Some(endpoint_url) => loader.endpoint_url(endpoint_url),
+        None => loader,
+    }
+    .load()
+    .await
 }
 
-/// Return AWS SDK config from id and secret with optional region name to use.
+/// Return AWS SDK config from id and secret with optional region name and endpoint to use.
 #[inline]
 #[must_use]
 async fn get_aws_config_from_credentials(
     id: &str,
     secret: &str,
     region: Option<String>,
+    endpoint_url: Option<String>,
 ) -> SdkConfig {
     let credentials_provider = Credentials::new(id, secret, None, None, "manual");
-    aws_config::from_env()
+    let loader = aws_config::from_env()
+        .region(get_region_provider(region))
+        .credentials_provider(credentials_provider);
+    match endpoint_url {
+        Some(endpoint_url) => loader.endpoint_url(endpoint_url),
+        None => loader,
+    }
+    .load()
+    .await
+}
+
+/// Return AWS SDK config using a web-identity (OIDC) token, for EKS/IRSA
+/// and other federated setups.
+async fn get_aws_config_from_web_identity(
+    web_identity_token_file: &str,
+    role_arn: &str,
+    region: Option<String>,
+    endpoint_url: Option<String>,
+) -> Result<SdkConfig, VaultError> {
+    let credentials_provider = WebIdentityTokenCredentialsProvider::builder()
+        .wi_session_name("nitor-vault")
+        .role_arn(role_arn)
+        .web_identity_token_file(web_identity_token_file)
+        .build();
+    credentials_provider
+        .provide_credentials()
+        .await
+        .map_err(VaultError::WebIdentityError)?;
+
+    let loader = aws_config::from_env()
         .region(get_region_provider(region))
-        .credentials_provider(credentials_provider)
-        .load()
+        .credentials_provider(credentials_provider);
+    Ok(match endpoint_url {
+        Some(endpoint_url) => loader.endpoint_url(endpoint_url),
+        None => loader,
+    }
+    .load()
+    .await)
+}
+
+/// Return AWS SDK config using the EC2/ECS instance-metadata-service
+/// credentials provider, instead of the default provider chain.
+async fn get_aws_config_from_imds(region: Option<String>, endpoint_url: Option<String>) -> SdkConfig {
+    let credentials_provider = ImdsCredentialsProvider::builder().build();
+    let loader = aws_config::from_env()
+        .region(get_region_provider(region))
+        .credentials_provider(credentials_provider);
+    match endpoint_url {
+        Some(endpoint_url) => loader.endpoint_url(endpoint_url),
+        None => loader,
+    }
+    .load()
+    .await
+}
+
+/// Return AWS SDK config with `base_config`'s credentials used to assume
+/// `role_arn` via STS, replacing them with the resulting temporary ones.
+async fn get_aws_config_from_assumed_role(
+    base_config: &SdkConfig,
+    role_arn: &str,
+    session_name: Option<String>,
+    external_id: Option<String>,
+    region: Option<String>,
+    endpoint_url: Option<String>,
+) -> Result<SdkConfig, VaultError> {
+    let session_name = session_name.unwrap_or_else(|| "nitor-vault".to_string());
+    let mut assume_role_provider = AssumeRoleProvider::builder(role_arn)
+        .session_name(session_name)
+        .configure(base_config);
+    if let Some(external_id) = external_id {
+        assume_role_provider = assume_role_provider.external_id(external_id);
+    }
+    let credentials_provider = assume_role_provider.build().await;
+    credentials_provider
+        .provide_credentials()
         .await
+        .map_err(VaultError::AssumeRoleError)?;
+
+    let loader = aws_config::from_env()
+        .region(get_region_provider(region))
+        .credentials_provider(credentials_provider);
+    Ok(match endpoint_url {
+        Some(endpoint_url) => loader.endpoint_url(endpoint_url),
+        None => loader,
+    }
+    .load()
+    .await)
 }
 
 #[inline]
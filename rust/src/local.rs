@@ -0,0 +1,241 @@
+//! Offline, passphrase-encrypted local vault backend.
+//!
+//! Keeps every secret in a single encrypted file on disk instead of
+//! S3 + KMS, so `store`/`lookup`/`delete`/`exists`/`all` work without any
+//! AWS account. The 256-bit data key is derived from a user passphrase
+//! with Argon2id; the serialized key -> value map is then encrypted as
+//! a whole with AES-256-GCM and written back atomically on every
+//! mutation, so this is meant for developer machines, air-gapped
+//! environments, and CI rather than highly concurrent access.
+//!
+//! File layout: `magic | version | salt | argon2 params | nonce | ciphertext+tag`
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::aes::{cipher, Aes256};
+use aes_gcm::{AesGcm, KeyInit, Nonce};
+use argon2::Argon2;
+use rand::Rng;
+
+use crate::errors::VaultError;
+use crate::value::Value;
+
+const MAGIC: &[u8; 4] = b"VLT1";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+/// `magic | version | salt | m_cost | t_cost | p_cost | nonce`
+const HEADER_LEN: usize = 4 + 1 + SALT_LEN + 4 + 4 + 4 + NONCE_LEN;
+
+/// Argon2id cost parameters, recorded in the file header so the same
+/// key can be re-derived from the passphrase on a later read.
+#[derive(Debug, Clone, Copy)]
+struct Argon2Params {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        // Heavier than the argon2 crate's own defaults since this key
+        // protects the whole vault file rather than a single login.
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// Offline vault backed by a single AES-256-GCM encrypted file.
+#[derive(Debug, Clone)]
+pub struct LocalVault {
+    path: PathBuf,
+    passphrase: String,
+}
+
+impl LocalVault {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, passphrase: String) -> Self {
+        Self {
+            path: path.into(),
+            passphrase,
+        }
+    }
+
+    /// Store `data` under `name`, creating the vault file if needed.
+    pub async fn store(&self, name: &str, data: &[u8]) -> Result<(), VaultError> {
+        let mut map = self.load_or_default().await?;
+        map.insert(name.to_string(), data.to_vec());
+        self.save(&map).await
+    }
+
+    /// Look up the value stored under `name`.
+    pub async fn lookup(&self, name: &str) -> Result<Value, VaultError> {
+        let map = self.load_or_default().await?;
+        map.get(name)
+            .map(|bytes| Value::new(bytes.clone()))
+            .ok_or_else(|| VaultError::LocalVaultKeyNotFoundError(name.to_string()))
+    }
+
+    /// Delete a single key.
+    pub async fn delete(&self, name: &str) -> Result<(), VaultError> {
+        self.delete_many(std::slice::from_ref(&name.to_string()))
+            .await
+    }
+
+    /// Delete several keys in one rewrite of the vault file.
+    pub async fn delete_many(&self, names: &[String]) -> Result<(), VaultError> {
+        let mut map = self.load_or_default().await?;
+        for name in names {
+            map.remove(name);
+        }
+        self.save(&map).await
+    }
+
+    /// Check whether `name` exists in the vault.
+    pub async fn exists(&self, name: &str) -> Result<bool, VaultError> {
+        Ok(self.load_or_default().await?.contains_key(name))
+    }
+
+    /// List every key currently stored.
+    pub async fn all(&self) -> Result<Vec<String>, VaultError> {
+        Ok(self.load_or_default().await?.into_keys().collect())
+    }
+
+    /// Load and decrypt the vault file, or an empty map if it doesn't exist yet.
+    async fn load_or_default(&self) -> Result<HashMap<String, Vec<u8>>, VaultError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let contents = std::fs::read(&self.path)
+            .map_err(|e| VaultError::FileReadError(self.path.display().to_string(), e))?;
+        self.decrypt(&contents)
+    }
+
+    /// Encrypt `map` under a fresh nonce and write it back atomically
+    /// via a temp-file-then-rename, so a crash mid-write can't corrupt
+    /// the existing vault file.
+    async fn save(&self, map: &HashMap<String, Vec<u8>>) -> Result<(), VaultError> {
+        let encoded = self.encrypt(map)?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| VaultError::FileReadError(parent.display().to_string(), e))?;
+            }
+        }
+        std::fs::write(&tmp_path, encoded)
+            .map_err(|e| VaultError::FileReadError(tmp_path.display().to_string(), e))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .map_err(|e| VaultError::FileReadError(self.path.display().to_string(), e))?;
+
+        Ok(())
+    }
+
+    fn derive_key(&self, salt: &[u8], params: Argon2Params) -> Result<[u8; KEY_LEN], VaultError> {
+        let argon2_params = argon2::Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| VaultError::LocalVaultKeyDerivationError(e.to_string()))?;
+
+        let argon2 = Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            argon2_params,
+        );
+
+        let mut key = [0u8; KEY_LEN];
+        argon2
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| VaultError::LocalVaultKeyDerivationError(e.to_string()))?;
+
+        Ok(key)
+    }
+
+    fn encrypt(&self, map: &HashMap<String, Vec<u8>>) -> Result<Vec<u8>, VaultError> {
+        let plaintext = serde_json::to_vec(map)?;
+
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill(&mut salt);
+        let params = Argon2Params::default();
+        let key = self.derive_key(&salt, params)?;
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::thread_rng().fill(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let cipher: AesGcm<Aes256, cipher::typenum::U12> = AesGcm::new_from_slice(&key)?;
+        let ciphertext = cipher
+            .encrypt(
+                nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: MAGIC,
+                },
+            )
+            .map_err(|_| VaultError::CiphertextEncryptionError)?;
+
+        let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&salt);
+        out.extend_from_slice(&params.memory_kib.to_be_bytes());
+        out.extend_from_slice(&params.iterations.to_be_bytes());
+        out.extend_from_slice(&params.parallelism.to_be_bytes());
+        out.extend_from_slice(&nonce_bytes);
+        out.extend_from_slice(&ciphertext);
+
+        Ok(out)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<HashMap<String, Vec<u8>>, VaultError> {
+        if data.len() < HEADER_LEN || &data[0..4] != MAGIC {
+            return Err(VaultError::LocalVaultCorruptHeaderError);
+        }
+        if data[4] != VERSION {
+            return Err(VaultError::LocalVaultCorruptHeaderError);
+        }
+
+        let salt = &data[5..5 + SALT_LEN];
+        let mut offset = 5 + SALT_LEN;
+        let memory_kib = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        let iterations = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        let parallelism = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        let nonce_bytes = &data[offset..offset + NONCE_LEN];
+        offset += NONCE_LEN;
+        let ciphertext = &data[offset..];
+
+        let params = Argon2Params {
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        let key = self.derive_key(salt, params)?;
+        let nonce = Nonce::from_slice(nonce_bytes);
+
+        let cipher: AesGcm<Aes256, cipher::typenum::U12> = AesGcm::new_from_slice(&key)?;
+        let plaintext = cipher
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad: MAGIC,
+                },
+            )
+            .map_err(|_| VaultError::LocalVaultDecryptError)?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
@@ -1,10 +1,12 @@
 mod cli;
 
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 
-use nitor_vault::Vault;
+use nitor_vault::{PresignTargets, Vault};
 
 #[allow(clippy::doc_markdown)]
 #[derive(Parser)]
@@ -36,6 +38,45 @@ struct Args {
     #[arg(long, name = "NAME", env = "VAULT_STACK")]
     vault_stack: Option<String>,
 
+    /// Override the S3 endpoint URL, for S3-compatible stores like MinIO or Garage
+    #[arg(long, value_name = "URL", env = "VAULT_S3_ENDPOINT")]
+    endpoint_url: Option<String>,
+
+    /// Use an offline, passphrase-encrypted local vault file instead of S3 + KMS.
+    /// Passphrase is read from `VAULT_LOCAL_PASSPHRASE`.
+    #[arg(long, value_name = "PATH", env = "VAULT_LOCAL_PATH")]
+    local: Option<String>,
+
+    /// Derive each secret's data key from this passphrase with Argon2id
+    /// instead of calling KMS, while still storing objects in S3 (or the
+    /// S3-compatible store given by `--endpoint-url`). Unlike `--local`, the bucket/prefix
+    /// layout is unchanged, so this is for skipping the KMS dependency
+    /// rather than skipping S3 too.
+    #[arg(long, value_name = "PASSPHRASE", env = "VAULT_KEY_PASSPHRASE")]
+    key_passphrase: Option<String>,
+
+    /// Base64-encoded 32-byte key to additionally have S3 encrypt objects
+    /// at rest with (SSE-C), on top of the client-side envelope encryption.
+    #[arg(long, value_name = "BASE64_KEY", env = "VAULT_SSE_C_KEY")]
+    sse_c_key: Option<String>,
+
+    /// Named profile to use from the shared AWS config/credentials files
+    #[arg(long, value_name = "NAME", env = "VAULT_PROFILE")]
+    profile: Option<String>,
+
+    /// IAM role ARN to assume via STS before talking to CloudFormation/KMS/S3,
+    /// for cross-account and federated (OIDC/web-identity) setups
+    #[arg(long, value_name = "ARN", env = "VAULT_ROLE_ARN")]
+    role_arn: Option<String>,
+
+    /// Session name to use for the `--role-arn` assume-role call
+    #[arg(long, value_name = "NAME", env = "VAULT_ROLE_SESSION_NAME")]
+    role_session_name: Option<String>,
+
+    /// External ID to use for the `--role-arn` assume-role call
+    #[arg(long, value_name = "ID", env = "VAULT_ROLE_EXTERNAL_ID")]
+    role_external_id: Option<String>,
+
     /// Suppress additional output and error messages
     #[arg(short, long)]
     quiet: bool,
@@ -50,7 +91,23 @@ struct Args {
 enum Command {
     /// List available secrets
     #[command(short_flag('a'), long_flag("all"), alias("a"))]
-    All {},
+    All {
+        /// Cache the result for this long (e.g. "30s", "5m", "1h") instead of
+        /// always listing from S3. Disabled by default.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        cache_ttl: Option<Duration>,
+
+        /// Ignore any cached entry and force a fresh list, still refreshing the cache
+        #[arg(long)]
+        refresh_cache: bool,
+    },
+
+    /// Manage the opt-in on-disk cache used by `lookup` and `--all`
+    #[command(long_flag("cache"))]
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
 
     /// Delete an existing key from the store
     #[command(short_flag('d'), long_flag("delete"), alias("d"))]
@@ -135,6 +192,46 @@ enum Command {
     #[command(long_flag("status"))]
     Status {},
 
+    /// Mount the vault as a read-only FUSE filesystem, one file per secret
+    #[cfg(feature = "mount")]
+    #[command(long_flag("mount"))]
+    Mount {
+        /// Directory to mount the vault at
+        mountpoint: String,
+    },
+
+    /// Export every secret in the vault into a single portable archive
+    #[command(
+        long_flag("export"),
+        long_about = "Serialize every secret in the vault into a single JSON archive,\n\
+                      suitable for `vault import`ing into another vault stack, account, or region.\n\n\
+                      Usage examples:\n\
+                      - Export to a file: `vault export backup.json`\n\
+                      - Export to stdout: `vault export -`"
+    )]
+    Export {
+        /// Output file, use '-' or omit for stdout
+        file: Option<String>,
+    },
+
+    /// Import every secret from an archive created by `vault export`
+    #[command(
+        long_flag("import"),
+        long_about = "Store every secret from a `vault export` archive, honoring\n\
+                      `--overwrite` the same way `vault store` does for each key.\n\n\
+                      Usage examples:\n\
+                      - Import from a file: `vault import backup.json`\n\
+                      - Import from stdin: `cat backup.json | vault import -`"
+    )]
+    Import {
+        /// Input file, use '-' for stdin
+        file: String,
+
+        /// Overwrite existing keys instead of skipping them as conflicts
+        #[arg(short = 'w', long)]
+        overwrite: bool,
+    },
+
     /// Initialize a new KMS key and S3 bucket
     #[command(
         short_flag('i'),
@@ -154,6 +251,57 @@ enum Command {
         name: Option<String>,
     },
 
+    /// Mint presigned GET URLs for a secret's S3 objects
+    #[command(
+        long_flag("presign"),
+        long_about = "Mint time-limited presigned GET URLs for a secret's S3 objects,\n\
+                      so a downstream system can fetch them without vault/AWS credentials\n\
+                      of its own. At least one of --key-object, --cipher or --meta must be given.\n\n\
+                      Usage examples:\n\
+                      - Share the wrapped data key and ciphertext for an hour:\n\
+                      \x20 `vault presign mykey --key-object --cipher --expires-in 1h`"
+    )]
+    Presign {
+        /// Key name to presign
+        key: String,
+
+        /// How long the URLs stay valid (e.g. "30s", "5m", "1h"). Defaults to 15 minutes.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "15m")]
+        expires_in: Duration,
+
+        /// Presign the `.key` object (the KMS-wrapped data key)
+        #[arg(long = "key-object")]
+        key_object: bool,
+
+        /// Presign the `.aesgcm.encrypted` object (the ciphertext)
+        #[arg(long)]
+        cipher: bool,
+
+        /// Presign the `.meta` object
+        #[arg(long)]
+        meta: bool,
+    },
+
+    /// Re-encrypt a secret under a freshly generated data key, or all
+    /// secrets if no key is given, without changing their plaintext values.
+    #[command(
+        long_flag("rotate"),
+        long_about = "Re-encrypt a secret (or, with no key given, every secret in the vault)\n\
+                      under a freshly generated KMS data key, without changing its value.\n\n\
+                      Usage examples:\n\
+                      - Rotate one key: `vault rotate mykey`\n\
+                      - Rotate every key: `vault --rotate`\n\
+                      - Migrate a key to a different CMK: `vault rotate mykey --new-key <ARN>`"
+    )]
+    Rotate {
+        /// Key name to rotate, or all keys if not given
+        key: Option<String>,
+
+        /// Migrate to this KMS key ARN instead of rotating under the current one
+        #[arg(long, name = "ARN")]
+        new_key: Option<String>,
+    },
+
     /// Update the vault CloudFormation stack.
     #[command(
         short_flag('u'),
@@ -180,6 +328,15 @@ enum Command {
         /// Optional output file
         #[arg(short, long, value_name = "filepath")]
         outfile: Option<String>,
+
+        /// Cache the result for this long (e.g. "30s", "5m", "1h") instead of
+        /// always looking up from S3. Disabled by default.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        cache_ttl: Option<Duration>,
+
+        /// Ignore any cached entry and force a fresh lookup, still refreshing the cache
+        #[arg(long)]
+        refresh_cache: bool,
     },
 
     /// Store a new key-value pair
@@ -225,12 +382,62 @@ enum Command {
         /// Overwrite existing key
         #[arg(short = 'w', long)]
         overwrite: bool,
+
+        /// Compress the value with zstd before encrypting it
+        #[arg(long)]
+        compress: bool,
+
+        /// Store the value split into content-addressed chunks, so
+        /// identical chunks shared with other versions or secrets are
+        /// only stored once. Not compatible with `--compress`.
+        #[arg(long, conflicts_with = "compress")]
+        chunked: bool,
     },
 }
 
+/// Subcommands for `vault --cache`.
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Remove every entry from the on-disk cache
+    Clear {},
+}
+
+/// Parse a duration given as a number followed by a unit suffix:
+/// `s` (seconds), `m` (minutes), `h` (hours) or `d` (days). A bare number is
+/// treated as seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split_at);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{value}'"))?;
+
+    let seconds = match unit {
+        "" | "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 60 * 60,
+        "d" => amount * 60 * 60 * 24,
+        other => {
+            return Err(format!(
+                "unknown duration unit '{other}', expected one of s/m/h/d"
+            ))
+        }
+    };
+
+    Ok(Duration::from_secs(seconds))
+}
+
 #[allow(clippy::match_same_arms)]
 #[allow(clippy::too_many_lines)]
 async fn run(args: Args) -> Result<()> {
+    if let Some(local_path) = args.local.clone() {
+        return run_local(local_path, args).await;
+    }
+
     if let Some(command) = args.command {
         match command {
             Command::Init { name } => {
@@ -238,6 +445,10 @@ async fn run(args: Args) -> Result<()> {
                     args.vault_stack.or(name),
                     args.region,
                     args.bucket,
+                    args.profile,
+                    args.role_arn,
+                    args.role_session_name,
+                    args.role_external_id,
                     args.quiet,
                 )
                 .await
@@ -250,6 +461,13 @@ async fn run(args: Args) -> Result<()> {
                     args.bucket,
                     args.key_arn,
                     args.prefix,
+                    args.endpoint_url,
+                    args.key_passphrase,
+                    args.sse_c_key,
+                    args.profile,
+                    args.role_arn,
+                    args.role_session_name,
+                    args.role_external_id,
                 )
                 .await
                 .with_context(|| "Failed to create vault from given params".red())?;
@@ -261,15 +479,42 @@ async fn run(args: Args) -> Result<()> {
             Command::Id {} => {
                 cli::print_aws_account(args.region).await?;
             }
+            Command::Cache { action } => match action {
+                CacheCommand::Clear {} => cli::cache_clear().await?,
+            },
+            #[cfg(feature = "mount")]
+            Command::Mount { mountpoint } => {
+                let vault = Vault::new(
+                    args.vault_stack,
+                    args.region,
+                    args.bucket,
+                    args.key_arn,
+                    args.prefix,
+                    args.endpoint_url,
+                    args.key_passphrase,
+                    args.sse_c_key,
+                    args.profile,
+                    args.role_arn,
+                    args.role_session_name,
+                    args.role_external_id,
+                )
+                .await
+                .with_context(|| "Failed to create vault from given params".red())?;
+                cli::mount(vault, &mountpoint).await?;
+            }
             // All other commands can use the same single Vault
-            Command::All {}
+            Command::All { .. }
             | Command::Decrypt { .. }
             | Command::Delete { .. }
             | Command::Describe {}
             | Command::Encrypt { .. }
             | Command::Exists { .. }
+            | Command::Export { .. }
+            | Command::Import { .. }
             | Command::Info {}
             | Command::Lookup { .. }
+            | Command::Presign { .. }
+            | Command::Rotate { .. }
             | Command::Status {}
             | Command::Store { .. } => {
                 let vault = Vault::new(
@@ -278,12 +523,22 @@ async fn run(args: Args) -> Result<()> {
                     args.bucket,
                     args.key_arn,
                     args.prefix,
+                    args.endpoint_url,
+                    args.key_passphrase,
+                    args.sse_c_key,
+                    args.profile,
+                    args.role_arn,
+                    args.role_session_name,
+                    args.role_external_id,
                 )
                 .await
                 .with_context(|| "Failed to create vault from given params".red())?;
 
                 match command {
-                    Command::All {} => cli::list_all_keys(&vault).await?,
+                    Command::All {
+                        cache_ttl,
+                        refresh_cache,
+                    } => cli::list_all_keys(&vault, cache_ttl, refresh_cache).await?,
                     Command::Delete { key } => cli::delete(&vault, &key).await?,
                     Command::Describe {} => println!("{}", vault.stack_info()),
                     Command::Decrypt {
@@ -304,6 +559,10 @@ async fn run(args: Args) -> Result<()> {
                             std::process::exit(1);
                         }
                     }
+                    Command::Export { file } => cli::export(&vault, file).await?,
+                    Command::Import { file, overwrite } => {
+                        cli::import(&vault, file, overwrite).await?;
+                    }
                     Command::Info {} => println!("{vault}"),
                     Command::Status {} => {
                         let status = vault.stack_status().await?;
@@ -311,11 +570,38 @@ async fn run(args: Args) -> Result<()> {
                             println!("{status}");
                         }
                     }
-                    Command::Lookup { key, outfile } => cli::lookup(&vault, &key, outfile).await?,
+                    Command::Lookup {
+                        key,
+                        outfile,
+                        cache_ttl,
+                        refresh_cache,
+                    } => cli::lookup(&vault, &key, outfile, cache_ttl, refresh_cache).await?,
+                    Command::Presign {
+                        key,
+                        expires_in,
+                        key_object,
+                        cipher,
+                        meta,
+                    } => {
+                        cli::presign(
+                            &vault,
+                            &key,
+                            expires_in,
+                            PresignTargets {
+                                key: key_object,
+                                cipher,
+                                meta,
+                            },
+                        )
+                        .await?;
+                    }
+                    Command::Rotate { key, new_key } => cli::rotate(&vault, key, new_key).await?,
                     Command::Store {
                         key,
                         value,
                         overwrite,
+                        compress,
+                        chunked,
                         file,
                         value_argument,
                     } => {
@@ -326,6 +612,8 @@ async fn run(args: Args) -> Result<()> {
                             file,
                             value_argument,
                             overwrite,
+                            compress,
+                            chunked,
                             args.quiet,
                         )
                         .await?;
@@ -335,6 +623,9 @@ async fn run(args: Args) -> Result<()> {
                     Command::Init { .. } => unreachable!(),
                     Command::Update { .. } => unreachable!(),
                     Command::Id { .. } => unreachable!(),
+                    Command::Cache { .. } => unreachable!(),
+                    #[cfg(feature = "mount")]
+                    Command::Mount { .. } => unreachable!(),
                 }
             }
         };
@@ -342,6 +633,64 @@ async fn run(args: Args) -> Result<()> {
     Ok(())
 }
 
+/// Run commands against an offline, passphrase-encrypted local vault file
+/// instead of S3 + KMS. Only the commands that make sense without a
+/// CloudFormation-backed stack are supported.
+async fn run_local(local_path: String, args: Args) -> Result<()> {
+    let passphrase = std::env::var("VAULT_LOCAL_PASSPHRASE")
+        .with_context(|| "VAULT_LOCAL_PASSPHRASE must be set when using --local".red())?;
+    let vault = nitor_vault::LocalVault::new(local_path, passphrase);
+
+    match args.command {
+        Some(Command::All {
+            cache_ttl: _,
+            refresh_cache: _,
+        }) => cli::local_list_all(&vault).await?,
+        Some(Command::Delete { key }) => cli::local_delete(&vault, &key).await?,
+        Some(Command::Exists { key }) => {
+            if !cli::local_exists(&vault, &key, args.quiet).await? {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Lookup {
+            key,
+            outfile,
+            cache_ttl: _,
+            refresh_cache: _,
+        }) => cli::local_lookup(&vault, &key, outfile).await?,
+        Some(Command::Store {
+            key,
+            value,
+            overwrite,
+            compress,
+            chunked,
+            file,
+            value_argument,
+        }) => {
+            if compress {
+                anyhow::bail!("--compress is not supported with --local".red())
+            }
+            if chunked {
+                anyhow::bail!("--chunked is not supported with --local".red())
+            }
+            cli::local_store(
+                &vault,
+                key,
+                value,
+                file,
+                value_argument,
+                overwrite,
+                args.quiet,
+            )
+            .await?;
+        }
+        Some(_) => anyhow::bail!("This command is not supported with --local".red()),
+        None => {}
+    }
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -0,0 +1,215 @@
+//! Read-only FUSE filesystem exposing vault secrets as files.
+//!
+//! Each stored key appears as a file in the mount root; reading it calls
+//! [`Vault::lookup`] and returns the decrypted value's bytes via
+//! [`Value::as_bytes`]. `Vault::lookup` has no caching of its own, so every
+//! `read()` does a full S3 `GetObject` + KMS decrypt, same as an uncached
+//! `vault lookup`. Listing the mount root calls [`Vault::all`] once, at
+//! mount time. This lets tools that only accept file paths (config loaders,
+//! TLS cert paths, ...) read secrets directly, without a plaintext temp
+//! file left on disk.
+//!
+//! Building this module requires the `mount` feature, since it pulls in
+//! the `fuser` FUSE binding that most users of this crate don't need.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory,
+    ReplyEntry, Request,
+};
+use tokio::runtime::Handle;
+
+use crate::errors::VaultError;
+use crate::{Value, Vault};
+
+/// How long the kernel may cache directory entries and attributes for.
+/// Secrets are re-read from the vault (decrypting again) on every `read()`
+/// regardless, so this only affects `lookup`/`getattr`.
+const ATTR_TTL: Duration = Duration::from_secs(1);
+const ROOT_INODE: u64 = 1;
+
+/// Mount `vault` read-only at `mountpoint`, blocking the calling thread
+/// until it is unmounted.
+///
+/// Must be called from within a Tokio runtime, since `Vault::all` and
+/// `Vault::lookup` are driven from the (synchronous) FUSE callback thread
+/// via [`Handle::block_on`].
+pub fn mount(vault: Vault, mountpoint: &Path) -> Result<(), VaultError> {
+    let filesystem = VaultFilesystem::new(vault, Handle::current())?;
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("vault".to_string()),
+    ];
+    fuser::mount2(filesystem, mountpoint, &options).map_err(VaultError::MountError)
+}
+
+struct VaultFilesystem {
+    vault: Vault,
+    handle: Handle,
+    /// Inode (starting at 2; 1 is the mount root) to key name.
+    names_by_inode: HashMap<u64, String>,
+    /// Decrypted value length per inode, resolved lazily (via `Vault::lookup`)
+    /// and cached at first `getattr`/`lookup`, so consumers that trust the
+    /// reported `st_size` (mmap, fstat-then-read loaders, `wc -c`, ...) see
+    /// the real size instead of an empty file.
+    sizes_by_inode: HashMap<u64, u64>,
+}
+
+impl VaultFilesystem {
+    fn new(vault: Vault, handle: Handle) -> Result<Self, VaultError> {
+        let names = handle.block_on(vault.all())?;
+        let names_by_inode = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (i as u64 + 2, name))
+            .collect();
+        Ok(Self {
+            vault,
+            handle,
+            names_by_inode,
+            sizes_by_inode: HashMap::new(),
+        })
+    }
+
+    /// Resolve and cache the decrypted size of the secret at `ino`,
+    /// decrypting it via `Vault::lookup` on the first call. Returns `0`
+    /// for an unknown inode or a secret that fails to decrypt, so a stale
+    /// entry still reports *some* size rather than erroring `getattr`.
+    fn size_for(&mut self, ino: u64) -> u64 {
+        if let Some(&size) = self.sizes_by_inode.get(&ino) {
+            return size;
+        }
+        let Some(name) = self.names_by_inode.get(&ino).cloned() else {
+            return 0;
+        };
+        let size = self
+            .handle
+            .block_on(self.vault.lookup(&name))
+            .map_or(0, |value| value.as_bytes().len() as u64);
+        self.sizes_by_inode.insert(ino, size);
+        size
+    }
+
+    fn attr_for(&self, ino: u64, size: u64) -> FileAttr {
+        let is_root = ino == ROOT_INODE;
+        FileAttr {
+            ino,
+            size,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind: if is_root {
+                FileType::Directory
+            } else {
+                FileType::RegularFile
+            },
+            perm: if is_root { 0o500 } else { 0o400 },
+            nlink: 1,
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+}
+
+impl Filesystem for VaultFilesystem {
+    fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let Some(name) = name.to_str() else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        if parent != ROOT_INODE {
+            reply.error(libc::ENOENT);
+            return;
+        }
+        let found = self
+            .names_by_inode
+            .iter()
+            .find(|(_, key_name)| key_name.as_str() == name)
+            .map(|(&ino, _)| ino);
+        match found {
+            Some(ino) => {
+                let size = self.size_for(ino);
+                reply.entry(&ATTR_TTL, &self.attr_for(ino, size), 0);
+            }
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
+        if ino == ROOT_INODE {
+            reply.attr(&ATTR_TTL, &self.attr_for(ino, 0));
+        } else if self.names_by_inode.contains_key(&ino) {
+            let size = self.size_for(ino);
+            reply.attr(&ATTR_TTL, &self.attr_for(ino, size));
+        } else {
+            reply.error(libc::ENOENT);
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(name) = self.names_by_inode.get(&ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.handle.block_on(self.vault.lookup(name)) {
+            Ok(value) => {
+                let bytes = value.as_bytes();
+                let offset = offset.max(0) as usize;
+                let end = offset.saturating_add(size as usize).min(bytes.len());
+                reply.data(bytes.get(offset..end).unwrap_or_default());
+            }
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        if ino != ROOT_INODE {
+            reply.error(libc::ENOENT);
+            return;
+        }
+
+        let mut entries = vec![
+            (ROOT_INODE, FileType::Directory, ".".to_string()),
+            (ROOT_INODE, FileType::Directory, "..".to_string()),
+        ];
+        entries.extend(
+            self.names_by_inode
+                .iter()
+                .map(|(&ino, name)| (ino, FileType::RegularFile, name.clone())),
+        );
+
+        for (i, (ino, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
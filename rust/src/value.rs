@@ -3,6 +3,7 @@ use std::path::Path;
 use std::{fmt, io};
 
 use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::errors::VaultError;
 
@@ -96,6 +97,25 @@ impl Value {
         }
     }
 
+    /// Read data from given filepath without blocking the async runtime.
+    ///
+    /// Supports both UTF-8 and non-UTF-8 contents.
+    pub async fn from_path_async(path: String) -> Result<Self, VaultError> {
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| VaultError::FileReadError(path, e))?;
+        Ok(Self::new(bytes))
+    }
+
+    /// Read data from stdin without blocking the async runtime.
+    ///
+    /// Supports both UTF-8 and non-UTF-8 input.
+    pub async fn from_stdin_async() -> Result<Self, VaultError> {
+        let mut bytes = Vec::new();
+        tokio::io::stdin().read_to_end(&mut bytes).await?;
+        Ok(Self::new(bytes))
+    }
+
     /// Returns the data as a byte slice `&[u8]`
     #[must_use]
     pub fn as_bytes(&self) -> &[u8] {
@@ -132,6 +152,24 @@ impl Value {
         writer.flush()
     }
 
+    /// Outputs the data directly to stdout without blocking the async runtime.
+    ///
+    /// String data is printed.
+    /// Binary data is outputted raw.
+    pub async fn output_to_stdout_async(&self) -> io::Result<()> {
+        let mut stdout = tokio::io::stdout();
+        stdout.write_all(self.as_bytes()).await?;
+        stdout.flush().await
+    }
+
+    /// Outputs the data to the specified file path without blocking the
+    /// async runtime.
+    pub async fn output_to_file_async(&self, path: &Path) -> io::Result<()> {
+        let mut file = tokio::fs::File::create(path).await?;
+        file.write_all(self.as_bytes()).await?;
+        file.flush().await
+    }
+
     #[must_use]
     /// Try to decode UTF-8 string from base64.
     pub fn decode_base64(self) -> Self {
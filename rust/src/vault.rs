@@ -4,30 +4,45 @@ use std::{env, fmt};
 use aes_gcm::aead::{Aead, Payload};
 use aes_gcm::aes::{cipher, Aes256};
 use aes_gcm::{AesGcm, KeyInit, Nonce};
-use aws_config::meta::region::RegionProviderChain;
-use aws_config::Region;
+use argon2::Argon2;
+use aws_config::{Region, SdkConfig};
 use aws_sdk_cloudformation::operation::describe_stacks::DescribeStacksOutput;
 use aws_sdk_cloudformation::types::{Capability, Parameter, StackStatus};
 use aws_sdk_cloudformation::Client as CloudFormationClient;
 use aws_sdk_kms::primitives::Blob;
 use aws_sdk_kms::types::DataKeySpec;
 use aws_sdk_kms::Client as KmsClient;
-use aws_sdk_s3::operation::put_object::PutObjectOutput;
-use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::types::Delete;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_sts::Client as stsClient;
 use base64::Engine;
 use colored::Colorize;
 use rand::Rng;
+use sha2::{Digest, Sha256};
 use tokio::time::Duration;
 
+use crate::backend::{S3Backend, StorageBackend};
 use crate::errors::VaultError;
 use crate::template::{template, VAULT_STACK_VERSION};
 use crate::value::Value;
-use crate::{CloudFormationParams, CloudFormationStackData, EncryptObject, Meta, S3DataKeys};
+use crate::{
+    Argon2KeyWrapParams, ChunkManifest, CloudFormationParams, CloudFormationStackData,
+    EncryptObject, Meta, PresignTargets, PresignedSecretUrls, S3DataKeys, KEY_WRAP_ARGON2,
+    KEY_WRAP_KMS,
+};
 
 const WAIT_ANIMATION_DURATION: Duration = Duration::from_millis(1000);
+/// Default zstd compression level used when `--compress` is passed to `store`.
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+/// Chunk size used by `--chunked` storage.
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+/// Argon2id cost parameters for deriving a secret's data-key-wrapping key
+/// from `--key-passphrase`. Heavier than the `argon2` crate's own
+/// defaults since each derivation protects a single secret's data key
+/// rather than a login.
+const ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+const ARGON2_ITERATIONS: u32 = 3;
+const ARGON2_PARALLELISM: u32 = 4;
+const ARGON2_SALT_LEN: usize = 16;
 
 #[derive(Debug)]
 pub struct Vault {
@@ -39,7 +54,14 @@ pub struct Vault {
     cloudformation_params: CloudFormationParams,
     cf: CloudFormationClient,
     kms: KmsClient,
-    s3: S3Client,
+    /// Object transport for the KMS-wrapped secret blobs.
+    /// Defaults to [`S3Backend`], pointed at `endpoint` when given so
+    /// S3-compatible stores like MinIO or Garage can be used instead.
+    backend: Box<dyn StorageBackend>,
+    /// When set, data keys are derived from this passphrase with Argon2id
+    /// instead of being generated and wrapped by KMS. Only affects the
+    /// crypto path; `backend` is still used for storage either way.
+    key_passphrase: Option<String>,
 }
 
 impl Vault {
@@ -49,22 +71,69 @@ impl Vault {
     ///
     /// The Default trait can't be implemented for Vault since it can fail.
     pub async fn default() -> Result<Self, VaultError> {
-        Self::new(None, None, None, None, None).await
+        Self::new(
+            None, None, None, None, None, None, None, None, None, None, None, None,
+        )
+        .await
     }
 
     /// Construct Vault with optional arguments for an existing stack.
     /// This will try reading environment variables for the config values if they are `None`.
+    ///
+    /// When `key_passphrase` is set (or `VAULT_KEY_PASSPHRASE` is), secrets
+    /// are encrypted and decrypted with an Argon2id-derived key instead of
+    /// KMS; see [`Self::encrypt_with_passphrase`].
+    ///
+    /// When `sse_c_key` is set (or `VAULT_SSE_C_KEY` is), it must be a
+    /// base64-encoded 32-byte key; every S3 request is then sent with the
+    /// matching SSE-C headers so S3 additionally encrypts objects at rest
+    /// under a key it never stores, independent of the envelope encryption.
+    ///
+    /// `profile` (or `VAULT_PROFILE`) selects a named profile from the
+    /// shared AWS config/credentials files. `role_arn` (or
+    /// `VAULT_ROLE_ARN`) additionally assumes that IAM role via STS before
+    /// the CF/KMS/S3 clients are built, so role-chaining and OIDC/web-
+    /// identity setups don't need a wrapper script; `role_session_name`
+    /// and `role_external_id` (or `VAULT_ROLE_SESSION_NAME` /
+    /// `VAULT_ROLE_EXTERNAL_ID`) configure that assume-role call. The base
+    /// credentials `role_arn` assumes from can also come from a web-
+    /// identity token (EKS/IRSA) or the EC2/ECS instance-metadata service;
+    /// see [`build_aws_config`] for the `VAULT_WEB_IDENTITY_*`/
+    /// `VAULT_USE_IMDS`/`VAULT_IAM_ID`/`VAULT_IAM_SECRET` env vars that
+    /// select those, since there's no CLI flag for them yet.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault_stack: Option<String>,
         region: Option<String>,
         bucket: Option<String>,
         key: Option<String>,
         prefix: Option<String>,
+        endpoint: Option<String>,
+        key_passphrase: Option<String>,
+        sse_c_key: Option<String>,
+        profile: Option<String>,
+        role_arn: Option<String>,
+        role_session_name: Option<String>,
+        role_external_id: Option<String>,
     ) -> Result<Self, VaultError> {
-        let config = aws_config::from_env()
-            .region(get_region_provider(region))
-            .load()
-            .await;
+        let key_passphrase = key_passphrase.or_else(|| get_env_variable("VAULT_KEY_PASSPHRASE"));
+        let sse_c_key = sse_c_key
+            .or_else(|| get_env_variable("VAULT_SSE_C_KEY"))
+            .map(|encoded| decode_sse_c_key(&encoded))
+            .transpose()?;
+
+        let config = build_aws_config(
+            region,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await?;
+
+        // Allow overriding the S3 endpoint to support S3-compatible
+        // object stores (MinIO, Garage, Ceph, ...) instead of AWS S3.
+        let endpoint = endpoint.or_else(|| get_env_variable("VAULT_S3_ENDPOINT"));
 
         let region = config
             .region()
@@ -93,26 +162,54 @@ impl Vault {
             CloudFormationParams::from_stack(&cf_client, stack_name).await?
         };
 
+        let s3_client_builder = aws_sdk_s3::config::Builder::from(&config);
+        let s3_client_builder = match &endpoint {
+            Some(endpoint) => s3_client_builder
+                .endpoint_url(endpoint)
+                .force_path_style(true),
+            None => s3_client_builder,
+        };
+        let mut backend = S3Backend::new(
+            S3Client::from_conf(s3_client_builder.build()),
+            cloudformation_params.bucket_name.clone(),
+        );
+        if let Some(sse_c_key) = &sse_c_key {
+            backend = backend.with_sse_c_key(sse_c_key);
+        }
+
         Ok(Self {
             region,
             prefix,
             cloudformation_params,
             cf: cf_client,
             kms: KmsClient::new(&config),
-            s3: S3Client::new(&config),
+            backend: Box::new(backend),
+            key_passphrase,
         })
     }
 
     /// Initialize new Vault stack
+    ///
+    /// See [`Self::new`] for the meaning of `profile`, `role_arn`,
+    /// `role_session_name` and `role_external_id`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn init(
         vault_stack: Option<String>,
         region: Option<String>,
         bucket: Option<String>,
+        profile: Option<String>,
+        role_arn: Option<String>,
+        role_session_name: Option<String>,
+        role_external_id: Option<String>,
     ) -> Result<(), VaultError> {
-        let config = aws_config::from_env()
-            .region(get_region_provider(region))
-            .load()
-            .await;
+        let config = build_aws_config(
+            region,
+            profile,
+            role_arn,
+            role_session_name,
+            role_external_id,
+        )
+        .await?;
 
         let region = config
             .region()
@@ -219,29 +316,40 @@ impl Vault {
 
     /// Get all available secrets
     pub async fn all(&self) -> Result<Vec<String>, VaultError> {
-        let output = self
-            .s3
-            .list_objects_v2()
-            .bucket(&self.cloudformation_params.bucket_name)
-            .send()
-            .await?;
-
-        Ok(output
-            .contents()
-            .iter()
-            .filter_map(|object| -> Option<String> {
-                object.key().and_then(|key| {
-                    if key.ends_with(".aesgcm.encrypted") {
-                        key.strip_suffix(".aesgcm.encrypted")
-                            .map(std::borrow::ToOwned::to_owned)
-                    } else {
-                        None
-                    }
-                })
+        Ok(self
+            .backend
+            .list()
+            .await?
+            .into_iter()
+            .filter_map(|key| {
+                key.strip_suffix(".aesgcm.encrypted")
+                    .map(std::borrow::ToOwned::to_owned)
             })
             .collect::<Vec<_>>())
     }
 
+    /// Get all available secrets, invoking `on_names` with each page of
+    /// names as they arrive instead of waiting for the full listing, so a
+    /// caller (e.g. the CLI) can render results incrementally for vaults
+    /// with more than one page of objects.
+    pub async fn all_paged(
+        &self,
+        on_names: &mut (dyn FnMut(Vec<String>) + Send),
+    ) -> Result<(), VaultError> {
+        self.backend
+            .list_paged(&mut |page| {
+                on_names(
+                    page.into_iter()
+                        .filter_map(|key| {
+                            key.strip_suffix(".aesgcm.encrypted")
+                                .map(std::borrow::ToOwned::to_owned)
+                        })
+                        .collect(),
+                );
+            })
+            .await
+    }
+
     /// Get `CloudFormation` stack information
     #[must_use]
     pub fn stack_info(&self) -> CloudFormationParams {
@@ -251,39 +359,74 @@ impl Vault {
     /// Check if key already exists in bucket
     pub async fn exists(&self, name: &str) -> Result<bool, VaultError> {
         let name = self.full_key_name(name);
-        match self
-            .s3
-            .head_object()
-            .bucket(self.cloudformation_params.bucket_name.clone())
-            .key(format!("{name}.key"))
-            .send()
-            .await
-        {
-            Ok(_) => Ok(true),
-            Err(e) => {
-                let service_error = e.into_service_error();
-                if service_error.is_not_found() {
-                    // The object does not exist
-                    Ok(false)
-                } else {
-                    // Propagate other errors like networking or permissions
-                    Err(VaultError::S3HeadObjectError(service_error))
-                }
+        self.backend.exists(&format!("{name}.key")).await
+    }
+
+    /// Store encrypted data in S3.
+    ///
+    /// When `compress` is `true`, the value is zstd-compressed before
+    /// encryption; `lookup` detects this from the stored metadata and
+    /// transparently decompresses it, so it stays opt-in per call.
+    pub async fn store(&self, name: &str, data: &[u8], compress: bool) -> Result<(), VaultError> {
+        let encrypted = self.encrypt(data, compress, false).await?;
+        self.put_encrypted(name, encrypted).await
+    }
+
+    /// Store encrypted data in S3 split into content-addressed chunks.
+    ///
+    /// `data` is split into fixed-size [`CHUNK_SIZE`] chunks, each
+    /// encrypted under the vault's shared chunk data key (see
+    /// [`Self::chunk_data_key`]) with a nonce derived from the plaintext
+    /// chunk's digest, so identical chunks always re-encrypt to identical
+    /// ciphertext. Chunks already present at their content-addressed path
+    /// are not re-uploaded, so storing a new version that shares chunks
+    /// with a previous one, or with a different secret, only uploads what
+    /// changed. A manifest listing the chunk digests in order is then
+    /// stored like a regular value, so `lookup` can tell a chunked secret
+    /// apart from a regular one.
+    ///
+    /// `--compress` is not supported together with `--chunked`: chunk
+    /// boundaries are content-addressed by the plaintext digest, and
+    /// compressing per-chunk would rarely help while complicating dedup.
+    pub async fn store_chunked(&self, name: &str, data: &[u8]) -> Result<(), VaultError> {
+        let chunk_key = self.chunk_data_key().await?;
+        let cipher: AesGcm<Aes256, cipher::typenum::U12> =
+            AesGcm::new_from_slice(chunk_key.as_slice())?;
+
+        let mut chunks = Vec::new();
+        for plaintext_chunk in data.chunks(CHUNK_SIZE) {
+            let digest = Sha256::digest(plaintext_chunk);
+            let digest = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest);
+            let path = self.chunk_path(&digest);
+
+            if !self.backend.exists(&path).await? {
+                let nonce = chunk_nonce(&digest)?;
+                let ciphertext = cipher
+                    .encrypt(Nonce::from_slice(&nonce), plaintext_chunk)
+                    .map_err(|_| VaultError::CiphertextEncryptionError)?;
+                self.backend.put(&path, ciphertext).await?;
             }
+
+            chunks.push(digest);
         }
-    }
 
-    /// Store encrypted data in S3
-    pub async fn store(&self, name: &str, data: &[u8]) -> Result<(), VaultError> {
-        let encrypted = self.encrypt(data).await?;
+        let manifest = ChunkManifest {
+            chunks,
+            total_len: data.len() as u64,
+        };
+        let encrypted = self.encrypt(&serde_json::to_vec(&manifest)?, false, true).await?;
+        self.put_encrypted(name, encrypted).await
+    }
 
+    /// Write the `.key`/`.aesgcm.encrypted`/`.meta` triplet that makes up
+    /// an encrypted secret.
+    async fn put_encrypted(&self, name: &str, encrypted: EncryptObject) -> Result<(), VaultError> {
         let key = &self.full_key_name(name);
         let keys = S3DataKeys::new(key);
 
-        let put_cipher =
-            self.put_s3_object(keys.cipher, ByteStream::from(encrypted.aes_gcm_ciphertext));
-        let put_key = self.put_s3_object(keys.key, ByteStream::from(encrypted.data_key));
-        let put_meta = self.put_s3_object(keys.meta, ByteStream::from(encrypted.meta.into_bytes()));
+        let put_cipher = self.put_s3_object(keys.cipher, encrypted.aes_gcm_ciphertext);
+        let put_key = self.put_s3_object(keys.key, encrypted.data_key);
+        let put_meta = self.put_s3_object(keys.meta, encrypted.meta.into_bytes());
 
         tokio::try_join!(put_cipher, put_key, put_meta)?;
 
@@ -297,21 +440,108 @@ impl Vault {
         }
 
         let key = &self.full_key_name(name);
-        let identifiers = S3DataKeys::new(key).to_object_identifiers()?;
-        self.s3
-            .delete_objects()
-            .bucket(&self.cloudformation_params.bucket_name)
-            .delete(Delete::builder().set_objects(Some(identifiers)).build()?)
-            .send()
-            .await?;
+        let identifiers = S3DataKeys::new(key).as_array().map(str::to_string);
+        self.backend.delete_many(&identifiers).await
+    }
 
-        Ok(())
+    /// Mint presigned GET URLs for `name`'s S3 objects, selected via
+    /// `targets`, each valid for `expires_in`. Lets an operator hand a
+    /// downstream system a short-lived fetch link (plus, if `targets.key`
+    /// is set, the KMS-wrapped data key) so it can decrypt the secret
+    /// without vault/AWS credentials of its own.
+    pub async fn presign(
+        &self,
+        name: &str,
+        expires_in: Duration,
+        targets: PresignTargets,
+    ) -> Result<PresignedSecretUrls, VaultError> {
+        let key = &self.full_key_name(name);
+        let keys = S3DataKeys::new(key);
+
+        let key_url = if targets.key {
+            Some(self.backend.presign_get(&keys.key, expires_in).await?)
+        } else {
+            None
+        };
+        let cipher_url = if targets.cipher {
+            Some(self.backend.presign_get(&keys.cipher, expires_in).await?)
+        } else {
+            None
+        };
+        let meta_url = if targets.meta {
+            Some(self.backend.presign_get(&keys.meta, expires_in).await?)
+        } else {
+            None
+        };
+
+        Ok(PresignedSecretUrls {
+            key: key_url,
+            cipher: cipher_url,
+            meta: meta_url,
+        })
     }
 
     /// Return value for the given key name.
     /// If the data is valid UTF-8, it will be returned as a string.
     /// Otherwise, the raw bytes will be returned.
+    ///
+    /// Transparently reassembles secrets stored with `--chunked`.
     pub async fn lookup(&self, name: &str) -> Result<Value, VaultError> {
+        let (decrypted_bytes, chunked, _compress) = self.decrypt_raw(name).await?;
+        let decrypted_bytes = if chunked {
+            self.reassemble_chunks(&serde_json::from_slice(&decrypted_bytes)?)
+                .await?
+        } else {
+            decrypted_bytes
+        };
+        match String::from_utf8(decrypted_bytes) {
+            Ok(valid_string) => Ok(Value::Utf8(valid_string)),
+            Err(from_utf8_error) => Ok(Value::Binary(from_utf8_error.into_bytes())),
+        }
+    }
+
+    /// Re-encrypt a single secret under a freshly generated data key, without
+    /// changing its plaintext value.
+    ///
+    /// Pass `new_key_arn` to migrate the secret to a different KMS key
+    /// instead of just rotating it under the vault's configured one. The new
+    /// `.key`/`.aesgcm.encrypted`/`.meta` objects are only written once
+    /// encryption under the new data key has succeeded, so a failure before
+    /// that point leaves the existing ciphertext untouched.
+    ///
+    /// For a secret stored with `--chunked`, this only re-wraps the
+    /// manifest under the new data key; the content-addressed chunks it
+    /// references are left untouched, since they're encrypted under the
+    /// vault's shared chunk data key rather than `name`'s own one.
+    pub async fn rotate(&self, name: &str, new_key_arn: Option<&str>) -> Result<(), VaultError> {
+        let (data, chunked, compress) = self.decrypt_raw(name).await?;
+
+        let encrypted = match new_key_arn {
+            Some(key_arn) => self.encrypt_with_key(&data, compress, chunked, key_arn).await?,
+            None => self.encrypt(&data, compress, chunked).await?,
+        };
+
+        self.put_encrypted(name, encrypted).await
+    }
+
+    /// Rotate every secret currently in the vault, optionally onto a new KMS key.
+    ///
+    /// Returns the names that were rotated.
+    pub async fn rotate_all(&self, new_key_arn: Option<&str>) -> Result<Vec<String>, VaultError> {
+        let names = self.all().await?;
+        for name in &names {
+            self.rotate(name, new_key_arn).await?;
+        }
+        Ok(names)
+    }
+
+    /// Fetch, decrypt and decompress the raw bytes stored for `name`.
+    ///
+    /// Returns the plaintext bytes together with whether they were stored
+    /// chunked and whether they were stored zstd-compressed, so callers
+    /// that re-encrypt the value (like `rotate`) can preserve the original
+    /// `store`/`store_chunked` settings.
+    async fn decrypt_raw(&self, name: &str) -> Result<(Vec<u8>, bool, bool), VaultError> {
         let key = &self.full_key_name(name);
         let keys = S3DataKeys::new(key);
 
@@ -321,8 +551,13 @@ impl Vault {
         let (data_key, cipher_text, meta_add) = tokio::try_join!(data_key, cipher_text, meta_add)?;
 
         let meta: Meta = serde_json::from_slice(&meta_add)?;
+        let plaintext_key = if meta.key_wrap == KEY_WRAP_ARGON2 {
+            self.unwrap_data_key_with_passphrase(&data_key, &meta)?
+        } else {
+            self.direct_decrypt(&data_key).await?
+        };
         let cipher: AesGcm<Aes256, cipher::typenum::U12> =
-            AesGcm::new_from_slice(self.direct_decrypt(&data_key).await?.as_slice())?;
+            AesGcm::new_from_slice(plaintext_key.as_slice())?;
         let nonce = base64::engine::general_purpose::STANDARD.decode(meta.nonce)?;
         let nonce = Nonce::from_slice(nonce.as_slice());
         let decrypted_bytes = cipher
@@ -335,25 +570,20 @@ impl Vault {
             )
             .map_err(|_| VaultError::NonceDecryptError)?;
 
-        match String::from_utf8(decrypted_bytes) {
-            Ok(valid_string) => Ok(Value::Utf8(valid_string)),
-            Err(from_utf8_error) => Ok(Value::Binary(from_utf8_error.into_bytes())),
-        }
+        let compress = meta.compression == crate::COMPRESSION_ZSTD;
+        let decrypted_bytes = if compress {
+            zstd::bulk::decompress(&decrypted_bytes, meta.original_len as usize)
+                .map_err(VaultError::DecompressionError)?
+        } else {
+            decrypted_bytes
+        };
+
+        Ok((decrypted_bytes, meta.chunked, compress))
     }
 
-    /// Get S3 Object data for given key as a vec of bytes
+    /// Get object data for given key as a vec of bytes
     async fn get_s3_object(&self, key: String) -> Result<Vec<u8>, VaultError> {
-        self.s3
-            .get_object()
-            .bucket(self.cloudformation_params.bucket_name.clone())
-            .key(&key)
-            .send()
-            .await?
-            .body
-            .collect()
-            .await
-            .map_err(|_| VaultError::S3GetObjectBodyError)
-            .map(aws_sdk_s3::primitives::AggregatedBytes::to_vec)
+        self.backend.get(&key).await
     }
 
     /// Get decrypted data
@@ -368,17 +598,166 @@ impl Vault {
             .ok_or(VaultError::KMSDataKeyPlainTextMissingError)
     }
 
-    /// Encrypt data
-    async fn encrypt(&self, data: &[u8]) -> Result<EncryptObject, VaultError> {
+    /// Unwrap a data key that was AES-GCM-wrapped under an Argon2id-derived
+    /// passphrase key instead of KMS; see `encrypt_with_passphrase`.
+    fn unwrap_data_key_with_passphrase(
+        &self,
+        wrapped_data_key: &[u8],
+        meta: &Meta,
+    ) -> Result<Vec<u8>, VaultError> {
+        let passphrase = self
+            .key_passphrase
+            .as_deref()
+            .ok_or(VaultError::PassphraseRequiredError)?;
+        let params = meta
+            .argon2
+            .as_ref()
+            .ok_or(VaultError::LocalVaultCorruptHeaderError)?;
+
+        let salt = base64::engine::general_purpose::STANDARD.decode(&params.salt)?;
+        let wrap_key = derive_argon2_key(
+            passphrase,
+            &salt,
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+        )?;
+        let wrap_nonce = base64::engine::general_purpose::STANDARD.decode(&params.nonce)?;
+        let wrap_cipher: AesGcm<Aes256, cipher::typenum::U12> =
+            AesGcm::new_from_slice(&wrap_key)?;
+
+        wrap_cipher
+            .decrypt(Nonce::from_slice(&wrap_nonce), wrapped_data_key)
+            .map_err(|_| VaultError::NonceDecryptError)
+    }
+
+    /// Encrypt data under the vault's configured KMS key, or under an
+    /// Argon2id-derived key when a key passphrase is set (see
+    /// `encrypt_with_passphrase`), optionally zstd-compressing it first.
+    ///
+    /// `chunked` marks `data` as a [`ChunkManifest`] rather than the
+    /// secret's own value; see `store_chunked`.
+    async fn encrypt(
+        &self,
+        data: &[u8],
+        compress: bool,
+        chunked: bool,
+    ) -> Result<EncryptObject, VaultError> {
+        if let Some(passphrase) = self.key_passphrase.clone() {
+            return self
+                .encrypt_with_passphrase(data, compress, chunked, &passphrase)
+                .await;
+        }
+        let key_arn = self
+            .cloudformation_params
+            .key_arn
+            .clone()
+            .ok_or(VaultError::KeyARNMissingError)?;
+        self.encrypt_with_key(data, compress, chunked, &key_arn).await
+    }
+
+    /// Encrypt data under a key derived from `passphrase` with Argon2id,
+    /// instead of a KMS-generated data key.
+    ///
+    /// A fresh random 32-byte data key is generated locally and AES-GCM
+    /// wrapped under the Argon2id-derived key (with its own random salt
+    /// and nonce, recorded in `Meta` so `lookup` can re-derive it); the
+    /// wrapped bytes take the place of the KMS `ciphertext_blob` in the
+    /// `.key` object, and the payload itself is encrypted exactly like
+    /// the KMS path. This is selected with `--key-passphrase` or
+    /// `VAULT_KEY_PASSPHRASE`, and removes the hard dependency on AWS KMS
+    /// for the crypto path, e.g. for local development or air-gapped use.
+    async fn encrypt_with_passphrase(
+        &self,
+        data: &[u8],
+        compress: bool,
+        chunked: bool,
+        passphrase: &str,
+    ) -> Result<EncryptObject, VaultError> {
+        let mut data_key = [0u8; 32];
+        rand::thread_rng().fill(&mut data_key);
+
+        let original_len = data.len() as u64;
+        let (payload, compression) = if compress {
+            (
+                zstd::bulk::compress(data, DEFAULT_ZSTD_LEVEL)
+                    .map_err(VaultError::CompressionError)?,
+                crate::COMPRESSION_ZSTD,
+            )
+        } else {
+            (data.to_vec(), crate::COMPRESSION_NONE)
+        };
+
+        let mut salt = [0u8; ARGON2_SALT_LEN];
+        rand::thread_rng().fill(&mut salt);
+        let wrap_key = derive_argon2_key(
+            passphrase,
+            &salt,
+            ARGON2_MEMORY_KIB,
+            ARGON2_ITERATIONS,
+            ARGON2_PARALLELISM,
+        )?;
+        let wrap_nonce = create_random_nonce();
+        let wrap_cipher: AesGcm<Aes256, cipher::typenum::U12> =
+            AesGcm::new_from_slice(&wrap_key)?;
+        let wrapped_data_key = wrap_cipher
+            .encrypt(Nonce::from_slice(&wrap_nonce), data_key.as_slice())
+            .map_err(|_| VaultError::CiphertextEncryptionError)?;
+
+        let argon2_params = Argon2KeyWrapParams::new(
+            base64::engine::general_purpose::STANDARD.encode(salt),
+            ARGON2_MEMORY_KIB,
+            ARGON2_ITERATIONS,
+            ARGON2_PARALLELISM,
+            base64::engine::general_purpose::STANDARD.encode(wrap_nonce),
+        );
+
+        let aesgcm_cipher: AesGcm<Aes256, cipher::typenum::U12> =
+            AesGcm::new_from_slice(&data_key)?;
+        let nonce = create_random_nonce();
+        let nonce = Nonce::from_slice(nonce.as_slice());
+        let meta = Meta::aesgcm(
+            nonce,
+            compression,
+            original_len,
+            chunked,
+            KEY_WRAP_ARGON2,
+            Some(argon2_params),
+        )
+        .to_json()?;
+        let aes_gcm_ciphertext = aesgcm_cipher
+            .encrypt(
+                nonce,
+                Payload {
+                    msg: &payload,
+                    aad: meta.as_bytes(),
+                },
+            )
+            .map_err(|_| VaultError::CiphertextEncryptionError)?;
+
+        Ok(EncryptObject {
+            data_key: wrapped_data_key,
+            aes_gcm_ciphertext,
+            meta,
+        })
+    }
+
+    /// Encrypt data under an explicit KMS key ARN, optionally
+    /// zstd-compressing it first.
+    ///
+    /// Used by both `encrypt` (the vault's configured key) and `rotate`
+    /// (to support migrating a secret to a different key).
+    async fn encrypt_with_key(
+        &self,
+        data: &[u8],
+        compress: bool,
+        chunked: bool,
+        key_arn: &str,
+    ) -> Result<EncryptObject, VaultError> {
         let key_dict = self
             .kms
             .generate_data_key()
-            .key_id(
-                self.cloudformation_params
-                    .key_arn
-                    .clone()
-                    .ok_or(VaultError::KeyARNMissingError)?,
-            )
+            .key_id(key_arn)
             .key_spec(DataKeySpec::Aes256)
             .send()
             .await?;
@@ -387,16 +766,28 @@ impl Vault {
             .plaintext()
             .ok_or(VaultError::KMSDataKeyPlainTextMissingError)?;
 
+        let original_len = data.len() as u64;
+        let (payload, compression) = if compress {
+            (
+                zstd::bulk::compress(data, DEFAULT_ZSTD_LEVEL)
+                    .map_err(VaultError::CompressionError)?,
+                crate::COMPRESSION_ZSTD,
+            )
+        } else {
+            (data.to_vec(), crate::COMPRESSION_NONE)
+        };
+
         let aesgcm_cipher: AesGcm<Aes256, cipher::typenum::U12> =
             AesGcm::new_from_slice(plaintext.as_ref())?;
         let nonce = create_random_nonce();
         let nonce = Nonce::from_slice(nonce.as_slice());
-        let meta = Meta::aesgcm(nonce).to_json()?;
+        let meta = Meta::aesgcm(nonce, compression, original_len, chunked, KEY_WRAP_KMS, None)
+            .to_json()?;
         let aes_gcm_ciphertext = aesgcm_cipher
             .encrypt(
                 nonce,
                 Payload {
-                    msg: data,
+                    msg: &payload,
                     aad: meta.as_bytes(),
                 },
             )
@@ -416,20 +807,87 @@ impl Vault {
     }
 
     /// Send PUT request with the given byte data
-    async fn put_s3_object(
-        &self,
-        key: String,
-        body: ByteStream,
-    ) -> Result<PutObjectOutput, VaultError> {
-        Ok(self
-            .s3
-            .put_object()
-            .bucket(&self.cloudformation_params.bucket_name)
-            .key(key)
-            .acl(aws_sdk_s3::types::ObjectCannedAcl::Private)
-            .body(body)
-            .send()
-            .await?)
+    async fn put_s3_object(&self, key: String, body: Vec<u8>) -> Result<(), VaultError> {
+        self.backend.put(&key, body).await
+    }
+
+    /// Fetch, decrypt and concatenate every chunk referenced by `manifest`, in order.
+    async fn reassemble_chunks(&self, manifest: &ChunkManifest) -> Result<Vec<u8>, VaultError> {
+        let chunk_key = self.chunk_data_key().await?;
+        let cipher: AesGcm<Aes256, cipher::typenum::U12> =
+            AesGcm::new_from_slice(chunk_key.as_slice())?;
+
+        let mut data = Vec::with_capacity(manifest.total_len as usize);
+        for digest in &manifest.chunks {
+            let ciphertext = self.backend.get(&self.chunk_path(digest)).await?;
+            let nonce = chunk_nonce(digest)?;
+            let plaintext_chunk = cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                .map_err(|_| VaultError::NonceDecryptError)?;
+            data.extend_from_slice(&plaintext_chunk);
+        }
+
+        Ok(data)
+    }
+
+    /// Get the data key shared by every secret stored with `--chunked` in
+    /// this vault, generating and persisting it KMS-wrapped on first use.
+    ///
+    /// Chunks are content-addressed by the digest of their plaintext, so
+    /// dedup across secrets and versions only works if identical chunks
+    /// always encrypt to identical ciphertext; that requires all chunked
+    /// secrets to share one data key rather than each getting its own
+    /// freshly generated one.
+    ///
+    /// `--chunked` always wraps this shared key with KMS, so this returns
+    /// `ChunkedStoragePassphraseUnsupportedError` when `--key-passphrase`
+    /// is set: unlike a per-secret data key, it isn't
+    /// carried alongside a `Meta` sidecar that could record the Argon2
+    /// parameters needed to unwrap it again.
+    async fn chunk_data_key(&self) -> Result<Vec<u8>, VaultError> {
+        if self.key_passphrase.is_some() {
+            return Err(VaultError::ChunkedStoragePassphraseUnsupportedError);
+        }
+        let path = self.chunk_data_key_path();
+        if self.backend.exists(&path).await? {
+            let wrapped = self.backend.get(&path).await?;
+            self.direct_decrypt(&wrapped).await
+        } else {
+            let key_arn = self
+                .cloudformation_params
+                .key_arn
+                .clone()
+                .ok_or(VaultError::KeyARNMissingError)?;
+            let key_dict = self
+                .kms
+                .generate_data_key()
+                .key_id(key_arn)
+                .key_spec(DataKeySpec::Aes256)
+                .send()
+                .await?;
+            let plaintext = key_dict
+                .plaintext()
+                .ok_or(VaultError::KMSDataKeyPlainTextMissingError)?
+                .as_ref()
+                .to_vec();
+            let wrapped = key_dict
+                .ciphertext_blob()
+                .ok_or(VaultError::CiphertextEncryptionError)?
+                .to_owned()
+                .into_inner();
+            self.backend.put(&path, wrapped).await?;
+            Ok(plaintext)
+        }
+    }
+
+    /// Path of the KMS-wrapped shared chunk data key.
+    fn chunk_data_key_path(&self) -> String {
+        format!("{}chunks/.datakey", self.prefix)
+    }
+
+    /// Content-addressed path of the chunk whose plaintext has `digest`.
+    fn chunk_path(&self, digest: &str) -> String {
+        format!("{}chunks/{digest}.chunk", self.prefix)
     }
 
     /// Poll Cloudformation for stack status until it has been created or creation failed.
@@ -565,9 +1023,93 @@ fn create_random_nonce() -> [u8; 12] {
     nonce
 }
 
-/// Get AWS region from optional argument or fallback to default
-fn get_region_provider(region: Option<String>) -> RegionProviderChain {
-    RegionProviderChain::first_try(region.map(Region::new)).or_default_provider()
+/// Derive a chunk's AES-GCM nonce from its content digest, so identical
+/// plaintext chunks always re-encrypt to identical ciphertext.
+fn chunk_nonce(digest: &str) -> Result<[u8; 12], VaultError> {
+    let digest = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(digest)?;
+    digest[..12]
+        .try_into()
+        .map_err(|_| VaultError::NonceDecryptError)
+}
+
+/// Derive a 32-byte AES-256 key from `passphrase` and `salt` with Argon2id,
+/// used to wrap/unwrap a secret's data key when `--key-passphrase` is set.
+fn derive_argon2_key(
+    passphrase: &str,
+    salt: &[u8],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<[u8; 32], VaultError> {
+    let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(32))
+        .map_err(|e| VaultError::LocalVaultKeyDerivationError(e.to_string()))?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| VaultError::LocalVaultKeyDerivationError(e.to_string()))?;
+
+    Ok(key)
+}
+
+/// Decode a base64-encoded SSE-C key into the raw 32 bytes S3 expects.
+fn decode_sse_c_key(encoded: &str) -> Result<[u8; 32], VaultError> {
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+    decoded
+        .try_into()
+        .map_err(|decoded: Vec<u8>| VaultError::InvalidSseCustomerKeyLengthError(decoded.len()))
+}
+
+/// Build the `SdkConfig` shared by the CF/KMS/S3 clients, via the same
+/// [`crate::resolve_aws_config_from_args`] chain the standalone CLI
+/// resolver uses (static id/secret, web-identity/IRSA, IMDS, named profile,
+/// then optionally an `AssumeRole` on top), so `Vault::new`/`Vault::init`
+/// support web-identity and IMDS credentials too, not just profile and
+/// assume-role.
+///
+/// `profile`/`role_arn`/`role_session_name`/`role_external_id` fall back to
+/// `VAULT_PROFILE`/`VAULT_ROLE_ARN`/`VAULT_ROLE_SESSION_NAME`/
+/// `VAULT_ROLE_EXTERNAL_ID` respectively when not given; the web-identity/
+/// IMDS/static-credential inputs have no CLI flags of their own yet, so
+/// they're only read from `VAULT_IAM_ID`/`VAULT_IAM_SECRET`/
+/// `VAULT_WEB_IDENTITY_TOKEN_FILE`/`VAULT_WEB_IDENTITY_ROLE_ARN`/
+/// `VAULT_USE_IMDS`. The endpoint override is deliberately not passed
+/// through here: it only applies to the S3 client, which callers configure
+/// separately, while CloudFormation/KMS/STS always use their real AWS
+/// endpoints.
+async fn build_aws_config(
+    region: Option<String>,
+    profile: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    role_external_id: Option<String>,
+) -> Result<SdkConfig, VaultError> {
+    let iam_id = get_env_variable("VAULT_IAM_ID");
+    let iam_secret = get_env_variable("VAULT_IAM_SECRET");
+    let web_identity_token_file = get_env_variable("VAULT_WEB_IDENTITY_TOKEN_FILE");
+    let web_identity_role_arn = get_env_variable("VAULT_WEB_IDENTITY_ROLE_ARN");
+    let use_imds = get_env_variable("VAULT_USE_IMDS").is_some_and(|v| v == "true" || v == "1");
+    let profile = profile.or_else(|| get_env_variable("VAULT_PROFILE"));
+    let role_arn = role_arn.or_else(|| get_env_variable("VAULT_ROLE_ARN"));
+    let role_session_name =
+        role_session_name.or_else(|| get_env_variable("VAULT_ROLE_SESSION_NAME"));
+    let role_external_id = role_external_id.or_else(|| get_env_variable("VAULT_ROLE_EXTERNAL_ID"));
+
+    crate::resolve_aws_config_from_args(
+        region,
+        profile,
+        iam_id,
+        iam_secret,
+        None,
+        web_identity_token_file,
+        web_identity_role_arn,
+        use_imds,
+        role_arn,
+        role_session_name,
+        role_external_id,
+    )
+    .await
 }
 
 /// Return possible env variable value as Option